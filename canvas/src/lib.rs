@@ -747,11 +747,13 @@ impl Path2D {
 
     #[inline]
     pub fn quadratic_curve_to(&mut self, ctrl: Vector2F, to: Vector2F) {
+        self.ensure_subpath(ctrl);
         self.current_contour.push_quadratic(ctrl, to);
     }
 
     #[inline]
     pub fn bezier_curve_to(&mut self, ctrl0: Vector2F, ctrl1: Vector2F, to: Vector2F) {
+        self.ensure_subpath(ctrl0);
         self.current_contour.push_cubic(ctrl0, ctrl1, to);
     }
 
@@ -826,6 +828,13 @@ impl Path2D {
         self.outline
     }
 
+    // As the HTML canvas spec requires, starts a subpath at `point` if there isn't one.
+    fn ensure_subpath(&mut self, point: Vector2F) {
+        if self.current_contour.is_empty() {
+            self.current_contour.push_endpoint(point);
+        }
+    }
+
     fn flush_current_contour(&mut self) {
         if !self.current_contour.is_empty() {
             self.outline.push_contour(mem::replace(&mut self.current_contour, Contour::new()));
@@ -17,3 +17,14 @@ pub fn test_path2d_formatting() {
     path.close_path();
     assert_eq!(format!("{:?}", path), "M 0 1 L 2 3 L 4 5 z");
 }
+
+#[test]
+pub fn test_curves_without_move_to_start_at_control_point() {
+    let mut path = Path2D::new();
+    path.quadratic_curve_to(vec2f(1.0, 2.0), vec2f(3.0, 4.0));
+    assert_eq!(format!("{:?}", path), "M 1 2 Q 1 2 3 4");
+
+    let mut path = Path2D::new();
+    path.bezier_curve_to(vec2f(1.0, 2.0), vec2f(3.0, 4.0), vec2f(5.0, 6.0));
+    assert_eq!(format!("{:?}", path), "M 1 2 C 1 2 3 4 5 6");
+}
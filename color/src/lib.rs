@@ -36,6 +36,7 @@ impl ColorU {
         ColorU::from_u32(0)
     }
 
+    /// Unpacks a color from a word laid out as `0xRRGGBBAA`.
     #[inline]
     pub const fn from_u32(rgba: u32) -> ColorU {
         ColorU {
@@ -46,6 +47,15 @@ impl ColorU {
         }
     }
 
+    /// Packs this color into a word laid out as `0xRRGGBBAA`.
+    ///
+    /// The big-endian byte order of the result is `r, g, b, a`, the same as the in-memory layout of
+    /// `ColorU`.
+    #[inline]
+    pub const fn to_u32(&self) -> u32 {
+        ((self.r as u32) << 24) | ((self.g as u32) << 16) | ((self.b as u32) << 8) | self.a as u32
+    }
+
     #[inline]
     pub const fn black() -> ColorU {
         ColorU {
@@ -170,6 +180,25 @@ impl ColorF {
         ColorF(self.0 + (other.0 - self.0) * F32x4::splat(t))
     }
 
+    /// Multiplies the color channels by alpha.
+    #[inline]
+    pub fn premultiply(&self) -> ColorF {
+        let a = self.a();
+        ColorF(self.0 * F32x4::new(a, a, a, 1.0))
+    }
+
+    /// Divides the color channels by alpha, undoing `premultiply()`. Fully transparent colors
+    /// become transparent black.
+    #[inline]
+    pub fn unpremultiply(&self) -> ColorF {
+        let a = self.a();
+        if a == 0.0 {
+            return ColorF::transparent_black();
+        }
+        let a_recip = 1.0 / a;
+        ColorF(self.0 * F32x4::new(a_recip, a_recip, a_recip, 1.0))
+    }
+
     #[inline]
     pub fn r(&self) -> f32 {
         self.0[0]
@@ -272,3 +301,28 @@ pub fn rgbf(r: f32, g: f32, b: f32) -> ColorF {
 pub fn rgbaf(r: f32, g: f32, b: f32, a: f32) -> ColorF {
     ColorF::new(r, g, b, a)
 }
+
+#[cfg(test)]
+mod test {
+    use crate::ColorU;
+
+    #[test]
+    fn test_u32_round_trip() {
+        for &color in &[
+            ColorU::transparent_black(),
+            ColorU::black(),
+            ColorU::white(),
+            ColorU::new(0x12, 0x34, 0x56, 0x78),
+            ColorU::new(255, 0, 128, 1),
+        ] {
+            assert_eq!(ColorU::from_u32(color.to_u32()), color);
+        }
+    }
+
+    #[test]
+    fn test_u32_byte_layout() {
+        let color = ColorU::new(0x12, 0x34, 0x56, 0x78);
+        assert_eq!(color.to_u32(), 0x12345678);
+        assert_eq!(color.to_u32().to_be_bytes(), [color.r, color.g, color.b, color.a]);
+    }
+}
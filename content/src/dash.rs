@@ -19,7 +19,8 @@ const EPSILON: f32 = 0.0001;
 pub struct OutlineDash<'a> {
     input: &'a Outline,
     output: Outline,
-    state: DashState<'a>,
+    dashes: &'a [f32],
+    offset: f32,
 }
 
 impl<'a> OutlineDash<'a> {
@@ -36,9 +37,12 @@ impl<'a> OutlineDash<'a> {
     ///
     /// * `offset`: The line dash offset, or "phase". See
     ///   <https://developer.mozilla.org/en-US/docs/Web/API/CanvasRenderingContext2D/lineDashOffset>.
+    ///
+    /// The pattern restarts at the beginning of each contour. If the dash lengths add up to zero,
+    /// the stroke is left solid.
     #[inline]
     pub fn new(input: &'a Outline, dashes: &'a [f32], offset: f32) -> OutlineDash<'a> {
-        OutlineDash { input, output: Outline::new(), state: DashState::new(dashes, offset) }
+        OutlineDash { input, output: Outline::new(), dashes, offset }
     }
 
     /// Performs the dashing operation.
@@ -46,15 +50,16 @@ impl<'a> OutlineDash<'a> {
     /// The results can be retrieved with the `into_outline()` method.
     pub fn dash(&mut self) {
         for contour in &self.input.contours {
-            ContourDash::new(contour, &mut self.output, &mut self.state).dash()
+            let mut state = DashState::new(self.dashes, self.offset);
+            ContourDash::new(contour, &mut self.output, &mut state).dash();
+            if state.is_on() {
+                self.output.push_contour(state.output);
+            }
         }
     }
 
     /// Returns the resulting dashed outline.
-    pub fn into_outline(mut self) -> Outline {
-        if self.state.is_on() {
-            self.output.push_contour(self.state.output);
-        }
+    pub fn into_outline(self) -> Outline {
         self.output
     }
 }
@@ -124,11 +129,25 @@ struct DashState<'a> {
 
 impl<'a> DashState<'a> {
     fn new(dashes: &'a [f32], mut offset: f32) -> DashState<'a> {
+        // A pattern with no length would never advance, so draw one endless dash instead.
         let total: f32 = dashes.iter().cloned().sum();
+        if total <= 0.0 || total.is_nan() {
+            return DashState {
+                output: Contour::new(),
+                dashes,
+                current_dash_index: 0,
+                distance_left: f32::INFINITY,
+            };
+        }
+
         offset %= total;
+        if offset < 0.0 {
+            offset += total;
+        }
 
+        // Skip the dashes that the offset has consumed entirely.
         let mut current_dash_index = 0;
-        while current_dash_index < dashes.len() {
+        while current_dash_index < dashes.len() - 1 {
             let dash = dashes[current_dash_index];
             if offset < dash {
                 break;
@@ -141,7 +160,7 @@ impl<'a> DashState<'a> {
             output: Contour::new(),
             dashes,
             current_dash_index,
-            distance_left: offset,
+            distance_left: dashes[current_dash_index] - offset,
         }
     }
 
@@ -150,3 +169,83 @@ impl<'a> DashState<'a> {
         self.current_dash_index % 2 == 0
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::dash::OutlineDash;
+    use crate::outline::{Contour, Outline};
+    use pathfinder_geometry::vector::{Vector2F, vec2f};
+
+    // Dashes the polyline through the given points, and returns the endpoints of each dash.
+    fn dash_polyline(points: &[Vector2F], dashes: &[f32], offset: f32) -> Vec<Vec<Vector2F>> {
+        let mut contour = Contour::new();
+        points.iter().for_each(|&point| contour.push_endpoint(point));
+        let mut outline = Outline::new();
+        outline.push_contour(contour);
+
+        let mut dash = OutlineDash::new(&outline, dashes, offset);
+        dash.dash();
+        dash.into_outline().contours().iter().map(|contour| contour.points().to_vec()).collect()
+    }
+
+    #[test]
+    fn test_straight_line_dashes() {
+        let line = [vec2f(0.0, 0.0), vec2f(100.0, 0.0)];
+        let dashes = |offset| {
+            dash_polyline(&line, &[10.0, 10.0], offset).iter().map(|points| {
+                (points[0].x(), points[points.len() - 1].x())
+            }).collect::<Vec<_>>()
+        };
+
+        assert_eq!(dashes(0.0),
+                   vec![(0.0, 10.0), (20.0, 30.0), (40.0, 50.0), (60.0, 70.0), (80.0, 90.0)]);
+
+        // The offset shifts the pattern backwards along the line, wrapping around either way.
+        let shifted = vec![(0.0, 5.0), (15.0, 25.0), (35.0, 45.0), (55.0, 65.0), (75.0, 85.0),
+                           (95.0, 100.0)];
+        assert_eq!(dashes(5.0), shifted);
+        assert_eq!(dashes(25.0), shifted);
+        assert_eq!(dashes(-15.0), shifted);
+    }
+
+    #[test]
+    fn test_dashes_span_segments_and_zero_length_gaps() {
+        let corner = [vec2f(0.0, 0.0), vec2f(15.0, 0.0), vec2f(15.0, 20.0)];
+        assert_eq!(dash_polyline(&corner, &[20.0, 5.0], 0.0), vec![
+            vec![vec2f(0.0, 0.0), vec2f(15.0, 0.0), vec2f(15.0, 5.0)],
+            vec![vec2f(15.0, 10.0), vec2f(15.0, 20.0)],
+        ]);
+
+        // Dashes separated by gaps with no length abut.
+        let line = [vec2f(0.0, 0.0), vec2f(30.0, 0.0)];
+        assert_eq!(dash_polyline(&line, &[10.0, 0.0], 0.0), vec![
+            vec![vec2f(0.0, 0.0), vec2f(10.0, 0.0)],
+            vec![vec2f(10.0, 0.0), vec2f(20.0, 0.0)],
+            vec![vec2f(20.0, 0.0), vec2f(30.0, 0.0)],
+        ]);
+
+        // A pattern with no length at all leaves the line solid.
+        assert_eq!(dash_polyline(&line, &[0.0, 0.0], 0.0), vec![line.to_vec()]);
+    }
+
+    #[test]
+    fn test_dash_pattern_restarts_for_each_contour() {
+        let mut outline = Outline::new();
+        for &y in &[0.0, 10.0] {
+            let mut contour = Contour::new();
+            contour.push_endpoint(vec2f(0.0, y));
+            contour.push_endpoint(vec2f(15.0, y));
+            outline.push_contour(contour);
+        }
+
+        let mut dash = OutlineDash::new(&outline, &[10.0, 10.0], 0.0);
+        dash.dash();
+        let dashes: Vec<_> = dash.into_outline().contours().iter().map(|contour| {
+            contour.points().to_vec()
+        }).collect();
+        assert_eq!(dashes, vec![
+            vec![vec2f(0.0, 0.0), vec2f(10.0, 0.0)],
+            vec![vec2f(0.0, 10.0), vec2f(10.0, 10.0)],
+        ]);
+    }
+}
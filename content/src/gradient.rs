@@ -20,7 +20,6 @@ use pathfinder_simd::default::F32x2;
 use std::cmp::Ordering;
 use std::convert;
 use std::hash::{Hash, Hasher};
-use std::mem;
 
 /// A gradient, either linear or radial.
 #[derive(Clone, PartialEq, Debug)]
@@ -34,7 +33,10 @@ pub struct Gradient {
 
 /// A color in a gradient. Points in a gradient between two stops interpolate linearly between the
 /// stops.
-#[derive(Clone, Copy, PartialEq, Debug)]
+///
+/// Stops compare and hash equal if their offsets round to the same multiple of 1/65536, so that
+/// gradients computed along slightly different float paths still share a paint.
+#[derive(Clone, Copy, Debug)]
 pub struct ColorStop {
     /// The offset of the color stop, between 0.0 and 1.0 inclusive. The value 0.0 represents the
     /// start of the gradient, and 1.0 represents the end.
@@ -106,15 +108,26 @@ impl Hash for Gradient {
     }
 }
 
+impl PartialEq for ColorStop {
+    #[inline]
+    fn eq(&self, other: &ColorStop) -> bool {
+        self.color == other.color && self.quantized_offset() == other.quantized_offset()
+    }
+}
+
 impl Eq for ColorStop {}
 
 impl Hash for ColorStop {
     fn hash<H>(&self, state: &mut H) where H: Hasher {
-        unsafe {
-            self.color.hash(state);
-            let offset = mem::transmute::<f32, u32>(self.offset);
-            offset.hash(state);
-        }
+        self.color.hash(state);
+        self.quantized_offset().hash(state);
+    }
+}
+
+impl ColorStop {
+    #[inline]
+    fn quantized_offset(&self) -> i32 {
+        (self.offset * 65536.0).round() as i32
     }
 }
 
@@ -184,6 +197,9 @@ impl Gradient {
 
     /// Returns the value of the gradient at offset `t`, which will be clamped between 0.0 and 1.0.
     ///
+    /// Stops are interpolated in premultiplied alpha, so fading to a transparent stop doesn't
+    /// pick up that stop's color channels. The result is converted back to straight alpha.
+    ///
     /// FIXME(pcwalton): This should probably take `wrap` into account…
     pub fn sample(&self, mut t: f32) -> ColorU {
         if self.stops.is_empty() {
@@ -207,7 +223,9 @@ impl Gradient {
         }
 
         let ratio = ((t - lower_stop.offset) / denom).min(1.0);
-        lower_stop.color.to_f32().lerp(upper_stop.color.to_f32(), ratio).to_u8()
+        let (lower_color, upper_color) = (lower_stop.color.to_f32().premultiply(),
+                                          upper_stop.color.to_f32().premultiply());
+        lower_color.lerp(upper_color, ratio).unpremultiply().to_u8()
     }
 
     /// Returns true if all colors of all stops in this gradient are opaque (alpha is 1.0).
@@ -301,4 +319,40 @@ mod test {
             assert!(sample.r == 0, "{} {}", i, sample.r);
         }
     }
+
+    #[test]
+    fn interpolates_in_premultiplied_alpha() {
+        let mut grad = Gradient::linear_from_points(Vector2F::default(), Vector2F::default());
+        grad.add_color_stop(ColorU::new(255, 255, 255, 0), 0.0);
+        grad.add_color_stop(ColorU::new(255, 0, 0, 255), 1.0);
+
+        // Interpolating straight alpha would give a pinkish `(255, 128, 128, 128)` here.
+        let sample = grad.sample(0.5);
+        assert_eq!((sample.r, sample.g, sample.b), (255, 0, 0));
+        assert!(sample.a >= 127 && sample.a <= 128, "{:?}", sample);
+    }
+
+    #[test]
+    fn nearly_equal_stops_share_a_hash() {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::{Hash, Hasher};
+
+        let hash = |gradient: &Gradient| {
+            let mut hasher = DefaultHasher::new();
+            gradient.hash(&mut hasher);
+            hasher.finish()
+        };
+
+        let mut a = Gradient::linear_from_points(Vector2F::default(), Vector2F::default());
+        let mut b = a.clone();
+        a.add_color_stop(ColorU::black(), 0.3);
+        b.add_color_stop(ColorU::black(), 0.3 + 1.0e-6);
+        assert_ne!(a.stops()[0].offset, b.stops()[0].offset);
+        assert_eq!(a, b);
+        assert_eq!(hash(&a), hash(&b));
+
+        let mut c = Gradient::linear_from_points(Vector2F::default(), Vector2F::default());
+        c.add_color_stop(ColorU::black(), 0.31);
+        assert_ne!(a, c);
+    }
 }
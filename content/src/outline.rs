@@ -12,19 +12,25 @@
 
 use crate::clip::{self, ContourPolygonClipper};
 use crate::dilation::ContourDilator;
+use crate::fill::FillRule;
 use crate::orientation::Orientation;
 use crate::segment::{Segment, SegmentFlags, SegmentKind};
-use crate::util::safe_sqrt;
+use crate::util::{point_is_between, safe_sqrt};
 use pathfinder_geometry::line_segment::LineSegment2F;
 use pathfinder_geometry::rect::RectF;
 use pathfinder_geometry::transform2d::{Transform2F, Matrix2x2F};
 use pathfinder_geometry::transform3d::Perspective;
 use pathfinder_geometry::unit_vector::UnitVector;
 use pathfinder_geometry::vector::{Vector2F, vec2f};
+use pathfinder_simd::default::F32x4;
 use std::f32::consts::PI;
 use std::fmt::{self, Debug, Formatter};
 use std::mem;
 
+/// The maximum number of points, on-curve or control, that a single contour can hold and still be
+/// addressed by a `PointIndex`.
+pub const MAX_POINTS_PER_CONTOUR: usize = 0x0010_0000;
+
 /// A vector path to be filled. Outlines (a.k.a. paths) consist of *contours* (a.k.a. subpaths),
 /// which can be filled according to a fill rule.
 ///
@@ -47,6 +53,39 @@ pub struct Contour {
     pub(crate) closed: bool,
 }
 
+/// Builds an outline incrementally from path commands, without collecting its segments first.
+///
+/// Overlong contours are split exactly as `Outline::from_segments()` splits them.
+///
+/// ```
+/// use pathfinder_content::outline::OutlineBuilder;
+/// use pathfinder_geometry::vector::vec2f;
+///
+/// let mut builder = OutlineBuilder::new();
+/// builder.move_to(vec2f(0.0, 0.0));
+/// builder.line_to(vec2f(10.0, 0.0));
+/// builder.line_to(vec2f(5.0, 8.0));
+/// builder.close();
+/// let triangle = builder.build();
+/// assert_eq!(triangle.contours().len(), 1);
+/// assert_eq!(triangle.contours()[0].len(), 3);
+/// assert!(triangle.contours()[0].is_closed());
+/// ```
+pub struct OutlineBuilder {
+    outline: Outline,
+    current_contour: Contour,
+    // The index of the first piece of the current contour, if it has been split.
+    first_split_piece_index: Option<usize>,
+    max_points_per_contour: usize,
+    // Rather than updating the bounds of the contour as we push each point, accumulate them in
+    // SIMD registers and only convert them to rectangles once each contour is finished.
+    contour_bounds: BoundsAccumulator,
+    outline_bounds: BoundsAccumulator,
+    current_position: Option<Vector2F>,
+    subpath_start: Vector2F,
+    subpath_open: bool,
+}
+
 bitflags! {
     /// Flags that each point can have, indicating whether it is on-curve or whether it's a control
     /// point.
@@ -56,6 +95,9 @@ bitflags! {
         const CONTROL_POINT_0 = 0x01;
         /// This point is the second point of a quadratic Bézier curve.
         const CONTROL_POINT_1 = 0x02;
+        /// This control point belongs to a cubic Bézier curve approximating an arc. See
+        /// `SegmentFlags::FROM_ARC`.
+        const FROM_ARC = 0x04;
     }
 }
 
@@ -64,7 +106,8 @@ bitflags! {
     pub(crate) struct PushSegmentFlags: u8 {
         /// The bounds should be updated.
         const UPDATE_BOUNDS = 0x01;
-        /// The "from" point of the segme
+        /// The "from" point of the segment should be pushed even if the contour already ends
+        /// there. Otherwise, it's only pushed if the contour is empty.
         const INCLUDE_FROM_POINT = 0x02;
     }
 }
@@ -89,50 +132,29 @@ impl Outline {
     }
 
     /// Creates a new outline from a list of segments.
+    ///
+    /// Contours longer than `MAX_POINTS_PER_CONTOUR` points are split into several contours at
+    /// on-curve points. Pieces of a closed contour after the first start with a line from the
+    /// contour's first point, so that the pieces fill exactly the same area as the original. Note
+    /// that stroking a split contour will add caps or joins at the split points.
     #[inline]
     pub fn from_segments<I>(segments: I) -> Outline where I: Iterator<Item = Segment> {
-        let mut outline = Outline::new();
-        let mut current_contour = Contour::new();
+        Outline::from_segments_with_max_points_per_contour(segments, MAX_POINTS_PER_CONTOUR)
+    }
 
+    /// Like `from_segments()`, but splits contours longer than `max_points_per_contour` points.
+    ///
+    /// See `OutlineBuilder::set_max_points_per_contour()` for the limits on the value.
+    pub fn from_segments_with_max_points_per_contour<I>(segments: I,
+                                                        max_points_per_contour: usize)
+                                                        -> Outline
+                                                        where I: Iterator<Item = Segment> {
+        let mut builder = OutlineBuilder::new();
+        builder.set_max_points_per_contour(max_points_per_contour);
         for segment in segments {
-            if segment.flags.contains(SegmentFlags::FIRST_IN_SUBPATH) {
-                if !current_contour.is_empty() {
-                    outline
-                        .contours
-                        .push(mem::replace(&mut current_contour, Contour::new()));
-                }
-                current_contour.push_point(segment.baseline.from(), PointFlags::empty(), true);
-            }
-
-            if segment.flags.contains(SegmentFlags::CLOSES_SUBPATH) {
-                if !current_contour.is_empty() {
-                    current_contour.close();
-                    let contour = mem::replace(&mut current_contour, Contour::new());
-                    outline.push_contour(contour);
-                }
-                continue;
-            }
-
-            if segment.is_none() {
-                continue;
-            }
-
-            if !segment.is_line() {
-                current_contour.push_point(segment.ctrl.from(), PointFlags::CONTROL_POINT_0, true);
-                if !segment.is_quadratic() {
-                    current_contour.push_point(
-                        segment.ctrl.to(),
-                        PointFlags::CONTROL_POINT_1,
-                        true,
-                    );
-                }
-            }
-
-            current_contour.push_point(segment.baseline.to(), PointFlags::empty(), true);
+            builder.push_segment(&segment);
         }
-
-        outline.push_contour(current_contour);
-        outline
+        builder.build()
     }
 
     /// Creates a new outline that represents a single axis-aligned rectangle.
@@ -191,6 +213,35 @@ impl Outline {
         self.contours.push(contour);
     }
 
+    // Adds a subpath whose bounds were accumulated by `from_segments()`, and resets the contour
+    // bounds accumulator for the next subpath.
+    fn push_accumulated_contour(&mut self,
+                                mut contour: Contour,
+                                contour_bounds: &mut BoundsAccumulator,
+                                outline_bounds: &mut BoundsAccumulator) {
+        contour.bounds = contour_bounds.to_rect();
+        outline_bounds.push_bounds(*contour_bounds);
+        *contour_bounds = BoundsAccumulator::new();
+        self.contours.push(contour);
+    }
+
+    // Fixes up the pieces of a contour split by `from_segments()` once it is known whether the
+    // contour is closed. Every piece after the first begins with the first point of the contour;
+    // closed pieces need that point to fill the right area, but open ones don't.
+    fn finish_split_contour(&mut self, first_piece_index: usize, closed: bool) {
+        for (piece_index, piece) in self.contours[first_piece_index..].iter_mut().enumerate() {
+            if closed {
+                piece.closed = true;
+            } else if piece_index > 0 {
+                piece.points.remove(0);
+                piece.flags.remove(0);
+                let mut bounds = BoundsAccumulator::new();
+                piece.points.iter().for_each(|&point| bounds.push_point(point));
+                piece.bounds = bounds.to_rect();
+            }
+        }
+    }
+
     /// Removes the last subpath from this outline and returns it.
     pub fn pop_contour(&mut self) -> Option<Contour> {
         let last_contour = self.contours.pop();
@@ -248,6 +299,17 @@ impl Outline {
         self.bounds = self.bounds.dilate(amount);
     }
 
+    /// Moves every point of this outline, control points included, to the nearest multiple of
+    /// `cell_size`.
+    pub fn snap_to_grid(&mut self, cell_size: Vector2F) {
+        let mut new_bounds = None;
+        for contour in &mut self.contours {
+            contour.snap_to_grid(cell_size);
+            contour.update_bounds(&mut new_bounds);
+        }
+        self.bounds = new_bounds.unwrap_or_else(RectF::default);
+    }
+
     /// Returns true if this outline is obviously completely outside the closed polygon with the
     /// given vertices, via a quick check.
     ///
@@ -269,11 +331,152 @@ impl Outline {
             return;
         }
 
+        self.bounds = RectF::default();
         for contour in mem::replace(&mut self.contours, vec![]) {
             self.push_contour(ContourPolygonClipper::new(clip_polygon, contour).clip());
         }
     }
 
+    /// Returns a copy of this outline with all curves replaced by line segments that approximate
+    /// them to within the given tolerance.
+    pub fn flattened(&self, tolerance: f32) -> Outline {
+        let mut outline = Outline::with_capacity(self.contours.len());
+        for contour in &self.contours {
+            let mut flattened_contour = Contour::with_capacity(contour.len() as usize);
+            if let Some(first_position) = contour.first_position() {
+                flattened_contour.push_endpoint(first_position);
+            }
+            for segment in contour.iter(ContourIterFlags::IGNORE_CLOSE_SEGMENT) {
+                segment.flatten(tolerance, |line| flattened_contour.push_endpoint(line.to()));
+            }
+            if contour.is_closed() {
+                flattened_contour.close();
+            }
+            outline.push_contour(flattened_contour);
+        }
+        outline
+    }
+
+    /// Returns the fraction of the unit square centered on `point` that this outline covers under
+    /// the given fill rule, for antialiased hit testing.
+    ///
+    /// Contours are treated as closed, as they are when filled. Coverage is estimated by
+    /// supersampling a finely flattened copy of the outline, so a point well inside returns 1.0,
+    /// a point more than half a unit away from the outline returns 0.0, and a point on a straight
+    /// edge returns about 0.5.
+    pub fn coverage_at(&self, point: Vector2F, fill_rule: FillRule) -> f32 {
+        const SUBSAMPLES: usize = 16;
+        const FLATTENING_TOLERANCE: f32 = 0.01;
+
+        let square = RectF::new(point - vec2f(0.5, 0.5), vec2f(1.0, 1.0));
+        if self.is_empty() || !square.intersects(self.bounds) {
+            return 0.0;
+        }
+
+        let mut edges = vec![];
+        for contour in &self.flattened(FLATTENING_TOLERANCE).contours {
+            push_closed_edges(contour, &mut edges);
+        }
+
+        let mut covered_samples = 0;
+        let mut crossings = vec![];
+        for sample_y in 0..SUBSAMPLES {
+            let y = square.min_y() + (sample_y as f32 + 0.5) / SUBSAMPLES as f32;
+            crossings.clear();
+            crossings.extend(edges.iter().filter(|edge| {
+                (edge.from_y() <= y) != (edge.to_y() <= y)
+            }).map(|edge| {
+                let t = (y - edge.from_y()) / (edge.to_y() - edge.from_y());
+                let winding = if edge.to_y() > edge.from_y() { 1 } else { -1 };
+                (edge.from_x() + (edge.to_x() - edge.from_x()) * t, winding)
+            }));
+
+            for sample_x in 0..SUBSAMPLES {
+                let x = square.min_x() + (sample_x as f32 + 0.5) / SUBSAMPLES as f32;
+                let winding: i32 = crossings.iter()
+                                            .filter(|&&(crossing_x, _)| crossing_x <= x)
+                                            .map(|&(_, winding)| winding)
+                                            .sum();
+                let inside = match fill_rule {
+                    FillRule::Winding => winding != 0,
+                    FillRule::EvenOdd => winding % 2 != 0,
+                };
+                if inside {
+                    covered_samples += 1;
+                }
+            }
+        }
+        covered_samples as f32 / (SUBSAMPLES * SUBSAMPLES) as f32
+    }
+
+    /// Classifies each contour as an outer boundary or a hole by how deeply the other contours
+    /// nest around it, regardless of which way it is wound.
+    ///
+    /// A contour enclosed by an even number of others, including none, is outer, and one enclosed
+    /// by an odd number is a hole. Containment is tested at the first point of each contour, so
+    /// contours are assumed not to cross one another. The result has one entry per contour.
+    pub fn classify_contours(&self) -> Vec<ContourRole> {
+        const FLATTENING_TOLERANCE: f32 = 0.01;
+
+        let flattened = self.flattened(FLATTENING_TOLERANCE);
+        let edges: Vec<Vec<LineSegment2F>> = flattened.contours.iter().map(|contour| {
+            let mut edges = vec![];
+            push_closed_edges(contour, &mut edges);
+            edges
+        }).collect();
+
+        self.contours.iter().enumerate().map(|(contour_index, contour)| {
+            let point = match contour.first_position() {
+                None => return ContourRole::Outer,
+                Some(point) => point,
+            };
+
+            let depth = edges.iter().enumerate().filter(|&(other_index, other_edges)| {
+                other_index != contour_index &&
+                    flattened.contours[other_index].bounds().contains_point(point) &&
+                    other_edges.iter().filter(|edge| {
+                        (edge.from_y() <= point.y()) != (edge.to_y() <= point.y()) &&
+                            edge.solve_x_for_y(point.y()) > point.x()
+                    }).count() % 2 == 1
+            }).count();
+
+            if depth % 2 == 0 {
+                ContourRole::Outer
+            } else {
+                ContourRole::Hole
+            }
+        }).collect()
+    }
+
+    /// Estimates the number of line segments that flattening this outline to within the given
+    /// tolerance would produce, without actually flattening it.
+    ///
+    /// Curves are estimated with Wang's formula, which gives the number of uniform pieces needed.
+    /// Since `flattened()` subdivides by halving, its actual count may differ by up to a factor
+    /// of two either way. Lines count as one each. This is useful for sizing buffers and making
+    /// level-of-detail decisions.
+    pub fn estimated_flattened_vertices(&self, tolerance: f32) -> usize {
+        self.contours.iter().map(|contour| {
+            contour.iter(ContourIterFlags::empty())
+                   .map(|segment| estimated_flattened_segment_vertices(&segment, tolerance))
+                   .sum::<usize>()
+        }).sum()
+    }
+
+    /// Clips this outline against the given axis-aligned rectangle.
+    pub fn clip_against_rect(&mut self, clip_rect: RectF) {
+        if clip_rect.contains_rect(self.bounds) {
+            return;
+        }
+
+        self.clip_against_polygon(&[
+            clip_rect.origin(),
+            clip_rect.upper_right(),
+            clip_rect.lower_right(),
+            clip_rect.lower_left(),
+        ]);
+    }
+
     /// Marks all contours as closed.
     #[inline]
     pub fn close_all_contours(&mut self) {
@@ -286,6 +489,27 @@ impl Outline {
         self.contours.iter().all(Contour::is_empty)
     }
 
+    /// If this outline consists of a single closed contour tracing an axis-aligned rectangle with
+    /// four straight edges, returns that rectangle.
+    pub fn as_axis_aligned_rect(&self) -> Option<RectF> {
+        let contour = match self.contours[..] {
+            [ref contour] if contour.len() == 4 && contour.is_closed() => contour,
+            _ => return None,
+        };
+        if !(0..4).all(|point_index| contour.point_is_endpoint(point_index)) {
+            return None;
+        }
+
+        let (p0, p1) = (contour.position_of(0), contour.position_of(1));
+        let (p2, p3) = (contour.position_of(2), contour.position_of(3));
+        if (p0.x() == p1.x() && p1.y() == p2.y() && p2.x() == p3.x() && p3.y() == p0.y()) ||
+                (p0.y() == p1.y() && p1.x() == p2.x() && p2.y() == p3.y() && p3.x() == p0.x()) {
+            Some(RectF::from_points(p0.min(p2), p0.max(p2)))
+        } else {
+            None
+        }
+    }
+
     /// Returns the number of contours in this outline.
     #[inline]
     pub fn len(&self) -> usize {
@@ -308,6 +532,204 @@ impl Outline {
     }
 }
 
+impl OutlineBuilder {
+    /// Creates a new builder with an empty outline.
+    #[inline]
+    pub fn new() -> OutlineBuilder {
+        OutlineBuilder {
+            outline: Outline::new(),
+            current_contour: Contour::new(),
+            first_split_piece_index: None,
+            max_points_per_contour: MAX_POINTS_PER_CONTOUR,
+            contour_bounds: BoundsAccumulator::new(),
+            outline_bounds: BoundsAccumulator::new(),
+            current_position: None,
+            subpath_start: Vector2F::zero(),
+            subpath_open: false,
+        }
+    }
+
+    /// Sets how many points a contour can hold before it's split into several contours, as
+    /// `Outline::from_segments()` describes. The default is `MAX_POINTS_PER_CONTOUR`.
+    ///
+    /// Larger values are lowered to `MAX_POINTS_PER_CONTOUR`, since a `PointIndex` couldn't
+    /// address the points. Values below 5 are raised to 5, so that each piece can hold a cubic
+    /// curve after the two points it starts with.
+    #[inline]
+    pub fn set_max_points_per_contour(&mut self, max_points_per_contour: usize) {
+        self.max_points_per_contour = max_points_per_contour.clamp(5, MAX_POINTS_PER_CONTOUR);
+    }
+
+    /// Starts a new contour at the given point.
+    #[inline]
+    pub fn move_to(&mut self, to: Vector2F) {
+        self.current_position = Some(to);
+        self.subpath_start = to;
+        self.subpath_open = false;
+    }
+
+    /// Adds a line from the current point to the given point.
+    #[inline]
+    pub fn line_to(&mut self, to: Vector2F) {
+        let from = self.start_segment(to);
+        self.push_command_segment(Segment::line(LineSegment2F::new(from, to)));
+    }
+
+    /// Adds a quadratic Bézier curve from the current point to the given point.
+    #[inline]
+    pub fn quad_to(&mut self, ctrl: Vector2F, to: Vector2F) {
+        let from = self.start_segment(to);
+        self.push_command_segment(Segment::quadratic(LineSegment2F::new(from, to), ctrl));
+    }
+
+    /// Adds a cubic Bézier curve from the current point to the given point.
+    #[inline]
+    pub fn cubic_to(&mut self, ctrl0: Vector2F, ctrl1: Vector2F, to: Vector2F) {
+        let from = self.start_segment(to);
+        let ctrl = LineSegment2F::new(ctrl0, ctrl1);
+        self.push_command_segment(Segment::cubic(LineSegment2F::new(from, to), ctrl));
+    }
+
+    /// Closes the current contour. The current point returns to the start of the contour, and
+    /// further segments begin a new contour there.
+    #[inline]
+    pub fn close(&mut self) {
+        if self.subpath_open {
+            let mut segment = Segment::none();
+            segment.flags = SegmentFlags::CLOSES_SUBPATH;
+            self.push_segment(&segment);
+            self.subpath_open = false;
+        }
+        self.current_position = Some(self.subpath_start);
+    }
+
+    /// Finishes the last contour and returns the outline.
+    pub fn build(mut self) -> Outline {
+        if !self.current_contour.is_empty() {
+            let contour = mem::replace(&mut self.current_contour, Contour::new());
+            self.outline.push_accumulated_contour(contour,
+                                                  &mut self.contour_bounds,
+                                                  &mut self.outline_bounds);
+            if let Some(first_piece_index) = self.first_split_piece_index {
+                self.outline.finish_split_contour(first_piece_index, false);
+            }
+        }
+
+        self.outline.bounds = self.outline_bounds.to_rect();
+        self.outline
+    }
+
+    // Returns the point a new segment ending at `to` starts from. As in SVG and canvas, drawing
+    // without a current point starts at the segment's own endpoint.
+    fn start_segment(&mut self, to: Vector2F) -> Vector2F {
+        match self.current_position {
+            Some(from) => from,
+            None => {
+                self.move_to(to);
+                to
+            }
+        }
+    }
+
+    fn push_command_segment(&mut self, mut segment: Segment) {
+        if !self.subpath_open {
+            segment.flags.insert(SegmentFlags::FIRST_IN_SUBPATH);
+            self.subpath_open = true;
+        }
+        self.current_position = Some(segment.baseline.to());
+        self.push_segment(&segment);
+    }
+
+    fn push_segment(&mut self, segment: &Segment) {
+        let (outline, current_contour) = (&mut self.outline, &mut self.current_contour);
+        let (contour_bounds, outline_bounds) = (&mut self.contour_bounds, &mut self.outline_bounds);
+
+        if segment.flags.contains(SegmentFlags::FIRST_IN_SUBPATH) {
+            if !current_contour.is_empty() {
+                let contour = mem::replace(current_contour, Contour::new());
+                outline.push_accumulated_contour(contour, contour_bounds, outline_bounds);
+                if let Some(first_piece_index) = self.first_split_piece_index.take() {
+                    outline.finish_split_contour(first_piece_index, false);
+                }
+            }
+            current_contour.push_point(segment.baseline.from(), PointFlags::empty(), false);
+            contour_bounds.push_point(segment.baseline.from());
+        }
+
+        if segment.flags.contains(SegmentFlags::CLOSES_SUBPATH) {
+            if !current_contour.is_empty() {
+                current_contour.close();
+                let contour = mem::replace(current_contour, Contour::new());
+                outline.push_accumulated_contour(contour, contour_bounds, outline_bounds);
+                if let Some(first_piece_index) = self.first_split_piece_index.take() {
+                    outline.finish_split_contour(first_piece_index, true);
+                }
+            }
+            return;
+        }
+
+        if segment.is_none() {
+            return;
+        }
+
+        // A stream that doesn't mark its first segment as starting a subpath would otherwise
+        // begin the contour with a control point, so start it at the segment's origin instead.
+        if current_contour.is_empty() {
+            current_contour.push_point(segment.baseline.from(), PointFlags::empty(), false);
+            contour_bounds.push_point(segment.baseline.from());
+        }
+        debug_assert!(current_contour.point_is_endpoint(0));
+
+        let segment_point_count = match segment.kind {
+            SegmentKind::Quadratic => 2,
+            SegmentKind::Cubic => 3,
+            SegmentKind::None | SegmentKind::Line => 1,
+        };
+        if current_contour.points.len() + segment_point_count > self.max_points_per_contour {
+            let first_point = match self.first_split_piece_index {
+                Some(first_piece_index) => outline.contours[first_piece_index].points[0],
+                None => current_contour.points[0],
+            };
+            let split_point = current_contour.points[current_contour.points.len() - 1];
+            if self.first_split_piece_index.is_none() {
+                self.first_split_piece_index = Some(outline.contours.len());
+            }
+            let contour = mem::replace(current_contour, Contour::new());
+            outline.push_accumulated_contour(contour, contour_bounds, outline_bounds);
+            for &point in &[first_point, split_point] {
+                current_contour.push_point(point, PointFlags::empty(), false);
+                contour_bounds.push_point(point);
+            }
+        }
+
+        if !segment.is_line() {
+            let arc_flags = arc_point_flags(segment);
+            current_contour.push_point(segment.ctrl.from(),
+                                       PointFlags::CONTROL_POINT_0 | arc_flags,
+                                       false);
+            contour_bounds.push_point(segment.ctrl.from());
+            if !segment.is_quadratic() {
+                current_contour.push_point(
+                    segment.ctrl.to(),
+                    PointFlags::CONTROL_POINT_1 | arc_flags,
+                    false,
+                );
+                contour_bounds.push_point(segment.ctrl.to());
+            }
+        }
+
+        current_contour.push_point(segment.baseline.to(), PointFlags::empty(), false);
+        contour_bounds.push_point(segment.baseline.to());
+    }
+}
+
+impl Default for OutlineBuilder {
+    #[inline]
+    fn default() -> OutlineBuilder {
+        OutlineBuilder::new()
+    }
+}
+
 impl Debug for Outline {
     fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
         for (contour_index, contour) in self.contours.iter().enumerate() {
@@ -474,6 +896,14 @@ impl Contour {
         self.points.len() as u32
     }
 
+    /// Returns the number of on-curve points in this contour, not counting control points.
+    #[inline]
+    pub fn endpoint_count(&self) -> u32 {
+        self.flags.iter().filter(|flags| {
+            !flags.intersects(PointFlags::CONTROL_POINT_0 | PointFlags::CONTROL_POINT_1)
+        }).count() as u32
+    }
+
     /// Returns the dimensions of an axis-aligned rectangle that encloses this contour.
     #[inline]
     pub fn bounds(&self) -> RectF {
@@ -534,16 +964,22 @@ impl Contour {
 
     /// Adds a new quadratic Bézier curve to the given on-curve position and control point to this
     /// contour.
+    ///
+    /// The contour must not be empty, since contours start with an on-curve point.
     #[inline]
     pub fn push_quadratic(&mut self, ctrl: Vector2F, to: Vector2F) {
+        debug_assert!(!self.is_empty(), "a contour must start with an endpoint");
         self.push_point(ctrl, PointFlags::CONTROL_POINT_0, true);
         self.push_point(to, PointFlags::empty(), true);
     }
 
     /// Adds a new cubic Bézier curve to the given on-curve position and control points to this
     /// contour.
+    ///
+    /// The contour must not be empty, since contours start with an on-curve point.
     #[inline]
     pub fn push_cubic(&mut self, ctrl0: Vector2F, ctrl1: Vector2F, to: Vector2F) {
+        debug_assert!(!self.is_empty(), "a contour must start with an endpoint");
         self.push_point(ctrl0, PointFlags::CONTROL_POINT_0, true);
         self.push_point(ctrl1, PointFlags::CONTROL_POINT_1, true);
         self.push_point(to, PointFlags::empty(), true);
@@ -562,6 +998,8 @@ impl Contour {
                              flags: PointFlags,
                              update_bounds: bool) {
         debug_assert!(!point.x().is_nan() && !point.y().is_nan());
+        debug_assert!(!self.is_empty() ||
+                      !flags.intersects(PointFlags::CONTROL_POINT_0 | PointFlags::CONTROL_POINT_1));
 
         if update_bounds {
             let first = self.is_empty();
@@ -579,18 +1017,21 @@ impl Contour {
         }
 
         let update_bounds = flags.contains(PushSegmentFlags::UPDATE_BOUNDS);
-        self.push_point(segment.baseline.from(), PointFlags::empty(), update_bounds);
+        if flags.contains(PushSegmentFlags::INCLUDE_FROM_POINT) || self.is_empty() {
+            self.push_point(segment.baseline.from(), PointFlags::empty(), update_bounds);
+        }
 
         if !segment.is_line() {
+            let arc_flags = arc_point_flags(segment);
             self.push_point(
                 segment.ctrl.from(),
-                PointFlags::CONTROL_POINT_0,
+                PointFlags::CONTROL_POINT_0 | arc_flags,
                 update_bounds,
             );
             if !segment.is_quadratic() {
                 self.push_point(
                     segment.ctrl.to(),
-                    PointFlags::CONTROL_POINT_1,
+                    PointFlags::CONTROL_POINT_1 | arc_flags,
                     update_bounds,
                 );
             }
@@ -612,6 +1053,9 @@ impl Contour {
     ///
     /// * `direction`: Whether the arc should be drawn clockwise or counterclockwise from the +x
     ///   axis.
+    ///
+    /// The arc is converted to cubic curves irreversibly. Those curves are marked with
+    /// `SegmentFlags::FROM_ARC` when iterated.
     pub fn push_arc(&mut self,
                     transform: &Transform2F,
                     start_angle: f32,
@@ -662,8 +1106,9 @@ impl Contour {
             let rotation = Transform2F::from_rotation_vector(half_sweep_vector.rotate_by(vector));
             segment = segment.transform(&(*transform * direction_transform * rotation));
 
+            // Join the arc up to the end of the contour, unless it already starts there.
             let mut push_segment_flags = PushSegmentFlags::UPDATE_BOUNDS;
-            if segment_index == 0 {
+            if segment_index == 0 && self.last_position() != Some(segment.baseline.from()) {
                 push_segment_flags.insert(PushSegmentFlags::INCLUDE_FROM_POINT);
             }
             self.push_segment(&segment, push_segment_flags);
@@ -916,6 +1361,90 @@ impl Contour {
         self.bounds = self.bounds.dilate(amount);
     }
 
+    /// Moves every point of this contour, control points included, to the nearest multiple of
+    /// `cell_size`.
+    pub fn snap_to_grid(&mut self, cell_size: Vector2F) {
+        for (point_index, point) in self.points.iter_mut().enumerate() {
+            *point = (*point / cell_size).round() * cell_size;
+            union_rect(&mut self.bounds, *point, point_index == 0);
+        }
+    }
+
+    /// Replaces curves whose control points lie on their baseline with lines, and merges runs of
+    /// collinear lines into single lines.
+    ///
+    /// A point is only removed if it lies within `epsilon` of the line joining its neighbors and
+    /// between them, so the shape and winding of the contour are preserved.
+    pub fn merge_collinear(&mut self, epsilon: f32) {
+        if self.points.len() < 2 {
+            return;
+        }
+
+        let mut merged = Contour::with_capacity(self.points.len());
+        merged.closed = self.closed;
+        merged.push_point(self.points[0], PointFlags::empty(), true);
+
+        for segment in self.iter(ContourIterFlags::IGNORE_CLOSE_SEGMENT) {
+            let (from, to) = (segment.baseline.from(), segment.baseline.to());
+            let is_straight = segment.is_line() ||
+                (segment.is_quadratic() &&
+                 point_is_between(from, segment.ctrl.from(), to, epsilon)) ||
+                (segment.is_cubic() &&
+                 point_is_between(from, segment.ctrl.from(), to, epsilon) &&
+                 point_is_between(from, segment.ctrl.to(), to, epsilon));
+            if !is_straight {
+                let arc_flags = arc_point_flags(&segment);
+                merged.push_point(segment.ctrl.from(),
+                                  PointFlags::CONTROL_POINT_0 | arc_flags,
+                                  true);
+                if segment.is_cubic() {
+                    merged.push_point(segment.ctrl.to(),
+                                      PointFlags::CONTROL_POINT_1 | arc_flags,
+                                      true);
+                }
+                merged.push_point(to, PointFlags::empty(), true);
+                continue;
+            }
+
+            if merged.last_segment_is_line() {
+                let last_index = merged.points.len() - 1;
+                let prev = merged.points[last_index - 1];
+                if point_is_between(prev, merged.points[last_index], to, epsilon) {
+                    merged.points.pop();
+                    merged.flags.pop();
+                }
+            }
+            merged.push_point(to, PointFlags::empty(), true);
+        }
+
+        // Merge across the implicit closing line.
+        while merged.closed && merged.points.len() > 2 {
+            let last_index = merged.points.len() - 1;
+            let first = merged.points[0];
+            if merged.last_segment_is_line() &&
+                    point_is_between(merged.points[last_index - 1],
+                                     merged.points[last_index],
+                                     first,
+                                     epsilon) {
+                merged.points.pop();
+                merged.flags.pop();
+            } else if merged.flags[1].is_empty() &&
+                    point_is_between(merged.points[last_index], first, merged.points[1], epsilon) {
+                merged.points.remove(0);
+                merged.flags.remove(0);
+            } else {
+                break;
+            }
+        }
+
+        *self = merged;
+    }
+
+    #[inline]
+    fn last_segment_is_line(&self) -> bool {
+        self.points.len() >= 2 && self.flags[self.flags.len() - 2].is_empty()
+    }
+
     // Use this function to keep bounds up to date when mutating paths. See `Outline::transform()`
     // for an example of use.
     pub(crate) fn update_bounds(&self, bounds: &mut Option<RectF>) {
@@ -1027,7 +1556,13 @@ impl<'a> Iterator for ContourIter<'a> {
         let include_close_segment = self.contour.closed &&
             !self.flags.contains(ContourIterFlags::IGNORE_CLOSE_SEGMENT);
         if (self.index == contour.len() && !include_close_segment) ||
-                self.index == contour.len() + 1 {
+                self.index > contour.len() {
+            return None;
+        }
+
+        // A contour starting with a control point is malformed. Yield nothing rather than
+        // misreading its curves.
+        if !contour.point_is_endpoint(0) {
             return None;
         }
 
@@ -1046,21 +1581,27 @@ impl<'a> Iterator for ContourIter<'a> {
             return Some(Segment::line(LineSegment2F::new(point0, point1)));
         }
 
-        let point2_index = self.index;
+        // A curve that closes the contour ends at the first point, so wrap around. The iteration
+        // then stops, since this curve replaces the closing line.
+        let point2_index = contour.add_to_point_index(point1_index, 1);
         let point2 = contour.position_of(point2_index);
         self.index += 1;
         if contour.point_is_endpoint(point2_index) {
             return Some(Segment::quadratic(LineSegment2F::new(point0, point2), point1));
         }
 
-        let point3_index = self.index;
+        let point3_index = contour.add_to_point_index(point1_index, 2);
         let point3 = contour.position_of(point3_index);
         self.index += 1;
         debug_assert!(contour.point_is_endpoint(point3_index));
-        return Some(Segment::cubic(
+        let mut segment = Segment::cubic(
             LineSegment2F::new(point0, point3),
             LineSegment2F::new(point1, point2),
-        ));
+        );
+        if contour.flags_of(point1_index).contains(PointFlags::FROM_ARC) {
+            segment.flags.insert(SegmentFlags::FROM_ARC);
+        }
+        return Some(segment);
     }
 }
 
@@ -1073,6 +1614,16 @@ pub enum ArcDirection {
     CCW,
 }
 
+/// Whether a contour adds area to its outline or cuts a hole in it, as determined by
+/// `Outline::classify_contours()`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ContourRole {
+    /// The contour is enclosed by an even number of other contours, possibly none.
+    Outer,
+    /// The contour is enclosed by an odd number of other contours.
+    Hole,
+}
+
 bitflags! {
     /// Flags that control the behavior of `Contour::iter()`.
     pub struct ContourIterFlags: u8 {
@@ -1082,6 +1633,38 @@ bitflags! {
     }
 }
 
+// Accumulates the bounds of a set of points in a single SIMD register.
+//
+// The register is laid out as `(min_x, min_y, -max_x, -max_y)`, so that adding a point requires
+// only a single `min` operation and no branch for the first point.
+#[derive(Clone, Copy)]
+struct BoundsAccumulator(F32x4);
+
+impl BoundsAccumulator {
+    #[inline]
+    fn new() -> BoundsAccumulator {
+        BoundsAccumulator(F32x4::splat(f32::INFINITY))
+    }
+
+    #[inline]
+    fn push_point(&mut self, point: Vector2F) {
+        self.0 = self.0.min(point.0.concat_xy_xy(-point.0));
+    }
+
+    #[inline]
+    fn push_bounds(&mut self, other: BoundsAccumulator) {
+        self.0 = self.0.min(other.0);
+    }
+
+    #[inline]
+    fn to_rect(self) -> RectF {
+        if self.0[0] == f32::INFINITY {
+            return RectF::default();
+        }
+        RectF(self.0 * F32x4::new(1.0, 1.0, -1.0, -1.0))
+    }
+}
+
 #[inline]
 pub(crate) fn union_rect(bounds: &mut RectF, new_point: Vector2F, first: bool) {
     if first {
@@ -1090,3 +1673,422 @@ pub(crate) fn union_rect(bounds: &mut RectF, new_point: Vector2F, first: bool) {
         *bounds = bounds.union_point(new_point)
     }
 }
+
+// Wang's formula: a Bézier curve of degree n is within `tolerance` of a polyline with
+// `sqrt(n (n - 1) M / (8 tolerance))` uniformly spaced pieces, where M is the largest second
+// difference of its control points.
+fn estimated_flattened_segment_vertices(segment: &Segment, tolerance: f32) -> usize {
+    let (degree_factor, max_second_difference) = match segment.kind {
+        SegmentKind::None => return 0,
+        SegmentKind::Line => return 1,
+        SegmentKind::Quadratic => {
+            let (from, to) = (segment.baseline.from(), segment.baseline.to());
+            (2.0, (from - segment.ctrl.from() * 2.0 + to).length())
+        }
+        SegmentKind::Cubic => {
+            let (from, to) = (segment.baseline.from(), segment.baseline.to());
+            let (ctrl0, ctrl1) = (segment.ctrl.from(), segment.ctrl.to());
+            let second_difference = f32::max((from - ctrl0 * 2.0 + ctrl1).length(),
+                                             (ctrl0 - ctrl1 * 2.0 + to).length());
+            (6.0, second_difference)
+        }
+    };
+    let pieces = (degree_factor * max_second_difference / (8.0 * tolerance)).sqrt().ceil();
+    f32::max(pieces, 1.0) as usize
+}
+
+// Appends the edges of a flattened contour to `edges`, including the edge that closes it, since
+// contours are closed when filled.
+fn push_closed_edges(contour: &Contour, edges: &mut Vec<LineSegment2F>) {
+    edges.extend(contour.iter(ContourIterFlags::IGNORE_CLOSE_SEGMENT)
+                        .map(|segment| segment.baseline));
+    if let (Some(first), Some(last)) = (contour.first_position(), contour.last_position()) {
+        edges.push(LineSegment2F::new(last, first));
+    }
+}
+
+// Returns the flags, beyond the control point flags themselves, that the control points of the
+// given segment should carry.
+fn arc_point_flags(segment: &Segment) -> PointFlags {
+    if segment.flags.contains(SegmentFlags::FROM_ARC) {
+        PointFlags::FROM_ARC
+    } else {
+        PointFlags::empty()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::fill::FillRule;
+    use crate::outline::{ArcDirection, Contour, ContourIterFlags, ContourRole, Outline};
+    use crate::outline::{OutlineBuilder, PointFlags, PointIndex, MAX_POINTS_PER_CONTOUR};
+    use crate::segment::{Segment, SegmentFlags};
+    use pathfinder_geometry::line_segment::LineSegment2F;
+    use pathfinder_geometry::rect::RectF;
+    use pathfinder_geometry::transform2d::Transform2F;
+    use pathfinder_geometry::vector::{Vector2F, vec2f};
+    use std::f32::consts::{FRAC_1_SQRT_2, PI};
+
+    #[test]
+    fn test_from_segments_bounds_match_push_point() {
+        let mut segments = vec![];
+        let mut expected = Outline::new();
+        for contour_index in 0..64 {
+            let mut contour = Contour::new();
+            let origin = vec2f((contour_index * 37 % 101) as f32 - 50.0,
+                               (contour_index * 61 % 89) as f32 - 44.5);
+            let (p0, p1, p2, p3) = (origin,
+                                    origin + vec2f(3.5, -7.25),
+                                    origin + vec2f(-2.0, 9.0),
+                                    origin + vec2f(5.0, 1.0));
+            let mut first = Segment::line(LineSegment2F::new(p0, p1));
+            first.flags.insert(SegmentFlags::FIRST_IN_SUBPATH);
+            segments.push(first);
+            segments.push(Segment::cubic(LineSegment2F::new(p1, p3), LineSegment2F::new(p2, p0)));
+            contour.push_endpoint(p0);
+            contour.push_endpoint(p1);
+            contour.push_cubic(p2, p0, p3);
+            if contour_index % 2 == 0 {
+                let mut close = Segment::line(LineSegment2F::new(p3, p0));
+                close.flags.insert(SegmentFlags::CLOSES_SUBPATH);
+                segments.push(close);
+                contour.close();
+            }
+            expected.push_contour(contour);
+        }
+
+        let outline = Outline::from_segments(segments.into_iter());
+        assert_eq!(outline.bounds(), expected.bounds());
+        assert_eq!(outline.len(), expected.len());
+        for (contour, expected_contour) in outline.contours().iter().zip(expected.contours()) {
+            assert_eq!(contour.bounds(), expected_contour.bounds());
+        }
+    }
+
+    #[test]
+    fn test_merge_collinear_straightens_flat_cubic() {
+        let mut contour = Contour::new();
+        contour.push_endpoint(vec2f(0.0, 0.0));
+        contour.push_cubic(vec2f(2.0, 1.0), vec2f(6.0, 3.0), vec2f(8.0, 4.0));
+        contour.push_endpoint(vec2f(12.0, 6.0));
+        contour.push_endpoint(vec2f(12.0, 10.0));
+        contour.close();
+
+        contour.merge_collinear(0.001);
+        assert_eq!(contour.points(), &[vec2f(0.0, 0.0), vec2f(12.0, 6.0), vec2f(12.0, 10.0)]);
+        assert!((0..contour.len()).all(|index| contour.flags_of(index) == PointFlags::empty()));
+        assert!(contour.is_closed());
+
+        // A cubic that bulges away from its baseline must be kept.
+        let mut contour = Contour::new();
+        contour.push_endpoint(vec2f(0.0, 0.0));
+        contour.push_cubic(vec2f(2.0, 3.0), vec2f(6.0, 3.0), vec2f(8.0, 0.0));
+        contour.push_endpoint(vec2f(12.0, 0.0));
+        contour.merge_collinear(0.001);
+        assert_eq!(contour.len(), 5);
+    }
+
+    #[test]
+    fn test_from_segments_splits_overlong_contours() {
+        // A closed polygon approximating a circle, with more points than a contour can hold.
+        let point_count = MAX_POINTS_PER_CONTOUR + 50_000;
+        let points: Vec<_> = (0..point_count).map(|index| {
+            let angle = index as f32 / point_count as f32 * 2.0 * PI;
+            vec2f(angle.cos(), angle.sin()) * 1000.0
+        }).collect();
+        let segments = (0..point_count).map(|index| {
+            let next_index = (index + 1) % point_count;
+            let mut segment = Segment::line(LineSegment2F::new(points[index], points[next_index]));
+            if index == 0 {
+                segment.flags.insert(SegmentFlags::FIRST_IN_SUBPATH);
+            } else if next_index == 0 {
+                segment.flags.insert(SegmentFlags::CLOSES_SUBPATH);
+            }
+            segment
+        });
+
+        let outline = Outline::from_segments(segments);
+        assert_eq!(outline.len(), 2);
+        for (contour_index, contour) in outline.contours().iter().enumerate() {
+            assert!(contour.is_closed());
+            assert!(contour.len() as usize <= MAX_POINTS_PER_CONTOUR);
+            let last_index = PointIndex::new(contour_index as u32, contour.len() - 1);
+            assert_eq!(last_index.contour(), contour_index as u32);
+            assert_eq!(last_index.point(), contour.len() - 1);
+        }
+        assert_eq!(outline.contours()[1].position_of(0), points[0]);
+        assert_eq!(outline.bounds(), RectF::from_points(vec2f(-1000.0, -1000.0),
+                                                        vec2f(1000.0, 1000.0)));
+
+        // The pieces fill the same area as the original polygon.
+        assert_eq!(outline.coverage_at(vec2f(0.0, 0.0), FillRule::Winding), 1.0);
+        assert_eq!(outline.coverage_at(vec2f(-500.0, -300.0), FillRule::Winding), 1.0);
+        assert_eq!(outline.coverage_at(vec2f(999.0, 999.0), FillRule::Winding), 0.0);
+    }
+
+    #[test]
+    fn test_from_segments_splits_at_configured_limit() {
+        // Two laps around a square, open, so the pieces are consecutive chains.
+        let corners = [vec2f(0.0, 0.0), vec2f(10.0, 0.0), vec2f(10.0, 10.0), vec2f(0.0, 10.0)];
+        let segments = (0..8).map(|index| {
+            let line = LineSegment2F::new(corners[index % 4], corners[(index + 1) % 4]);
+            let mut segment = Segment::line(line);
+            if index == 0 {
+                segment.flags.insert(SegmentFlags::FIRST_IN_SUBPATH);
+            }
+            segment
+        });
+
+        let outline = Outline::from_segments_with_max_points_per_contour(segments, 5);
+        let lengths: Vec<_> = outline.contours().iter().map(|contour| contour.len()).collect();
+        assert_eq!(lengths, vec![5, 4, 2]);
+        assert!(outline.contours().iter().all(|contour| !contour.is_closed()));
+        for pieces in outline.contours().windows(2) {
+            assert_eq!(pieces[1].first_position(), pieces[0].last_position());
+        }
+    }
+
+    #[test]
+    fn test_arc_derived_cubics_carry_flag() {
+        let mut contour = Contour::new();
+        contour.push_endpoint(vec2f(10.0, 0.0));
+        contour.push_cubic(vec2f(20.0, 0.0), vec2f(20.0, 10.0), vec2f(30.0, 10.0));
+        contour.push_arc(&Transform2F::from_scale(10.0), 0.0, PI * 0.75, ArcDirection::CW);
+
+        let cubics: Vec<_> = contour.iter(ContourIterFlags::empty())
+                                    .filter(|segment| segment.is_cubic())
+                                    .collect();
+        assert_eq!(cubics.len(), 3);
+        assert!(!cubics[0].flags.contains(SegmentFlags::FROM_ARC));
+        assert!(cubics[1..].iter().all(|segment| segment.flags.contains(SegmentFlags::FROM_ARC)));
+
+        // The flag survives rebuilding the outline from segments.
+        let segments = contour.iter(ContourIterFlags::empty()).enumerate();
+        let segments = segments.map(|(index, mut segment)| {
+            if index == 0 {
+                segment.flags.insert(SegmentFlags::FIRST_IN_SUBPATH);
+            }
+            segment
+        });
+        let rebuilt = Outline::from_segments(segments);
+        let rebuilt_flags: Vec<_> = rebuilt.contours()[0].iter(ContourIterFlags::empty())
+                                                         .filter(|segment| segment.is_cubic())
+                                                         .map(|segment| segment.flags)
+                                                         .collect();
+        assert_eq!(rebuilt_flags.len(), 3);
+        assert!(!rebuilt_flags[0].contains(SegmentFlags::FROM_ARC));
+        assert!(rebuilt_flags[1..].iter().all(|flags| flags.contains(SegmentFlags::FROM_ARC)));
+    }
+
+    #[test]
+    fn test_full_circle_arcs_become_four_cubics() {
+        fn cubic_endpoints(contour: &Contour) -> Vec<Vector2F> {
+            let segments: Vec<_> = contour.iter(ContourIterFlags::IGNORE_CLOSE_SEGMENT).collect();
+            assert!(segments.iter().all(|segment| segment.is_cubic()), "{:?}", contour);
+            let mut endpoints = vec![segments[0].baseline.from()];
+            endpoints.extend(segments.iter().map(|segment| segment.baseline.to()));
+            endpoints
+        }
+
+        fn assert_near(actual: &[Vector2F], expected: &[Vector2F]) {
+            assert_eq!(actual.len(), expected.len(), "{:?}", actual);
+            for (&actual, &expected) in actual.iter().zip(expected.iter()) {
+                assert!((actual - expected).length() < 0.001, "{:?} != {:?}", actual, expected);
+            }
+        }
+
+        let mut contour = Contour::new();
+        let transform = Transform2F::from_translation(vec2f(20.0, 30.0)) *
+            Transform2F::from_scale(10.0);
+        contour.push_arc(&transform, 0.0, PI * 2.0, ArcDirection::CW);
+        let diagonal = 10.0 * FRAC_1_SQRT_2;
+        let endpoints: Vec<_> = [(1.0, -1.0), (1.0, 1.0), (-1.0, 1.0), (-1.0, -1.0), (1.0, -1.0)]
+            .iter()
+            .map(|&(x, y)| vec2f(20.0 + x * diagonal, 30.0 + y * diagonal))
+            .collect();
+        assert_near(&cubic_endpoints(&contour), &endpoints);
+
+        // SVG can only describe a full circle as two half circles.
+        let mut contour = Contour::new();
+        contour.push_endpoint(vec2f(30.0, 30.0));
+        let radius = vec2f(10.0, 10.0);
+        contour.push_svg_arc(radius, 0.0, false, ArcDirection::CW, vec2f(10.0, 30.0));
+        contour.push_svg_arc(radius, 0.0, false, ArcDirection::CW, vec2f(30.0, 30.0));
+        assert_near(&cubic_endpoints(&contour), &[
+            vec2f(30.0, 30.0), vec2f(20.0, 40.0), vec2f(10.0, 30.0), vec2f(20.0, 20.0),
+            vec2f(30.0, 30.0),
+        ]);
+    }
+
+    #[test]
+    fn test_coverage_at() {
+        let mut outline = Outline::from_rect(RectF::new(vec2f(10.0, 10.0), vec2f(20.0, 20.0)));
+        assert_eq!(outline.coverage_at(vec2f(20.0, 20.0), FillRule::Winding), 1.0);
+        assert_eq!(outline.coverage_at(vec2f(50.0, 20.0), FillRule::Winding), 0.0);
+        assert_eq!(outline.coverage_at(vec2f(20.0, 9.0), FillRule::Winding), 0.0);
+        assert_eq!(outline.coverage_at(vec2f(10.0, 20.0), FillRule::Winding), 0.5);
+        assert_eq!(outline.coverage_at(vec2f(30.0, 30.0), FillRule::Winding), 0.25);
+
+        // A second copy of the rect wound the same way leaves a hole under the even-odd rule.
+        outline.push_contour(outline.contours()[0].clone());
+        assert_eq!(outline.coverage_at(vec2f(20.0, 20.0), FillRule::Winding), 1.0);
+        assert_eq!(outline.coverage_at(vec2f(20.0, 20.0), FillRule::EvenOdd), 0.0);
+
+        // Curves are flattened finely enough that an edge still covers about half a pixel.
+        let mut circle = Contour::new();
+        circle.push_ellipse(&(Transform2F::from_translation(vec2f(20.0, 20.0)) *
+                              Transform2F::from_scale(8.0)));
+        circle.close();
+        let mut outline = Outline::new();
+        outline.push_contour(circle);
+        assert_eq!(outline.coverage_at(vec2f(20.0, 20.0), FillRule::Winding), 1.0);
+        let on_edge = outline.coverage_at(vec2f(28.0, 20.0), FillRule::Winding);
+        assert!((on_edge - 0.5).abs() < 0.05, "{}", on_edge);
+    }
+
+    #[test]
+    fn test_classify_contours_by_nesting() {
+        // A square with a round hole, an island inside the hole, and a separate square. All are
+        // wound the same way, so orientation alone can't tell the hole apart.
+        let mut hole = Contour::new();
+        hole.push_ellipse(&(Transform2F::from_translation(vec2f(50.0, 50.0)) *
+                            Transform2F::from_scale(30.0)));
+        hole.close();
+        let rects = [
+            RectF::new(vec2f(0.0, 0.0), vec2f(100.0, 100.0)),
+            RectF::new(vec2f(40.0, 40.0), vec2f(20.0, 20.0)),
+            RectF::new(vec2f(150.0, 0.0), vec2f(10.0, 10.0)),
+        ];
+        let [outer, island, separate] = rects.map(Contour::from_rect);
+
+        let mut outline = Outline::new();
+        for contour in [island, outer, separate, hole] {
+            outline.push_contour(contour);
+        }
+        assert_eq!(outline.classify_contours(),
+                   vec![ContourRole::Outer, ContourRole::Outer, ContourRole::Outer,
+                        ContourRole::Hole]);
+    }
+
+    #[test]
+    fn test_estimated_flattened_vertices() {
+        let rect = Outline::from_rect(RectF::new(vec2f(0.0, 0.0), vec2f(10.0, 20.0)));
+        assert_eq!(rect.estimated_flattened_vertices(0.1), 4);
+
+        let mut circle = Contour::new();
+        circle.push_ellipse(&Transform2F::from_scale(50.0));
+        circle.close();
+        let mut outline = Outline::new();
+        outline.push_contour(circle);
+
+        let coarse = outline.estimated_flattened_vertices(1.0);
+        let fine = outline.estimated_flattened_vertices(0.1);
+        let finer = outline.estimated_flattened_vertices(0.01);
+        assert!(coarse > 4);
+        assert!(coarse < fine && fine < finer);
+
+        let flattened = outline.flattened(0.1).contours()[0].len() as usize;
+        assert!(flattened >= fine / 2 && flattened <= fine * 2);
+    }
+
+    #[test]
+    fn test_outline_builder_matches_from_segments() {
+        let mut builder = OutlineBuilder::new();
+        builder.move_to(vec2f(0.0, 0.0));
+        builder.line_to(vec2f(10.0, 0.0));
+        builder.quad_to(vec2f(15.0, 5.0), vec2f(10.0, 10.0));
+        builder.close();
+        builder.line_to(vec2f(-5.0, 5.0));
+        builder.move_to(vec2f(20.0, 20.0));
+        builder.cubic_to(vec2f(25.0, 20.0), vec2f(30.0, 25.0), vec2f(30.0, 30.0));
+        let built = builder.build();
+
+        let first = |mut segment: Segment| {
+            segment.flags.insert(SegmentFlags::FIRST_IN_SUBPATH);
+            segment
+        };
+        let mut close = Segment::none();
+        close.flags = SegmentFlags::CLOSES_SUBPATH;
+        let segments = vec![
+            first(Segment::line(LineSegment2F::new(vec2f(0.0, 0.0), vec2f(10.0, 0.0)))),
+            Segment::quadratic(LineSegment2F::new(vec2f(10.0, 0.0), vec2f(10.0, 10.0)),
+                               vec2f(15.0, 5.0)),
+            close,
+            first(Segment::line(LineSegment2F::new(vec2f(0.0, 0.0), vec2f(-5.0, 5.0)))),
+            first(Segment::cubic(LineSegment2F::new(vec2f(20.0, 20.0), vec2f(30.0, 30.0)),
+                                 LineSegment2F::new(vec2f(25.0, 20.0), vec2f(30.0, 25.0)))),
+        ];
+        let expected = Outline::from_segments(segments.into_iter());
+
+        assert_eq!(format!("{:?}", built), format!("{:?}", expected));
+        assert_eq!(built.bounds(), expected.bounds());
+        assert_eq!(built.contours().len(), 3);
+        assert!(built.contours()[0].is_closed());
+        assert!(!built.contours()[1].is_closed());
+    }
+
+    #[test]
+    fn test_segment_after_reconstructs_closing_cubic() {
+        // The control points of the final cubic are the last points in the array, and its
+        // endpoint is the first point of the contour.
+        let mut contour = Contour::new();
+        contour.push_endpoint(vec2f(0.0, 0.0));
+        contour.push_endpoint(vec2f(10.0, 0.0));
+        contour.push_endpoint(vec2f(10.0, 10.0));
+        contour.push_point(vec2f(5.0, 15.0), PointFlags::CONTROL_POINT_0, true);
+        contour.push_point(vec2f(-5.0, 5.0), PointFlags::CONTROL_POINT_1, true);
+        contour.close();
+        assert_eq!(contour.len(), 5);
+
+        let segment = contour.segment_after(2);
+        assert!(segment.is_cubic());
+        assert_eq!(segment.baseline, LineSegment2F::new(vec2f(10.0, 10.0), vec2f(0.0, 0.0)));
+        assert_eq!(segment.ctrl, LineSegment2F::new(vec2f(5.0, 15.0), vec2f(-5.0, 5.0)));
+
+        // Iteration yields the same closing cubic, and no closing line after it.
+        let segments: Vec<_> = contour.iter(ContourIterFlags::empty()).collect();
+        assert_eq!(segments.len(), 3);
+        assert!(segments[2].is_cubic());
+        assert_eq!((segments[2].baseline, segments[2].ctrl), (segment.baseline, segment.ctrl));
+    }
+
+    #[test]
+    fn test_from_segments_starts_contours_with_endpoint() {
+        // The first segment doesn't start a subpath, so there's no explicit starting point.
+        let segment = Segment::cubic(LineSegment2F::new(vec2f(0.0, 0.0), vec2f(10.0, 0.0)),
+                                     LineSegment2F::new(vec2f(2.0, 5.0), vec2f(8.0, 5.0)));
+        let outline = Outline::from_segments(vec![segment].into_iter());
+        let contour = &outline.contours()[0];
+        assert_eq!(contour.len(), 4);
+        assert!(contour.point_is_endpoint(0));
+        assert_eq!(contour.position_of(0), vec2f(0.0, 0.0));
+        let segments: Vec<_> = contour.iter(ContourIterFlags::empty()).collect();
+        assert_eq!(segments.len(), 1);
+        assert_eq!((segments[0].baseline, segments[0].ctrl), (segment.baseline, segment.ctrl));
+    }
+
+    #[test]
+    fn test_contour_starting_with_control_point_is_rejected() {
+        let contour = Contour {
+            points: vec![vec2f(5.0, 5.0), vec2f(10.0, 0.0)],
+            flags: vec![PointFlags::CONTROL_POINT_0, PointFlags::empty()],
+            bounds: RectF::new(vec2f(5.0, 0.0), vec2f(5.0, 5.0)),
+            closed: true,
+        };
+        assert_eq!(contour.iter(ContourIterFlags::empty()).count(), 0);
+    }
+
+    #[test]
+    fn test_endpoint_count_skips_control_points() {
+        let mut contour = Contour::new();
+        assert_eq!(contour.endpoint_count(), 0);
+        contour.push_endpoint(vec2f(0.0, 0.0));
+        for index in 0..3 {
+            let x = index as f32 * 10.0;
+            contour.push_cubic(vec2f(x + 2.0, 5.0), vec2f(x + 8.0, 5.0), vec2f(x + 10.0, 0.0));
+        }
+        contour.push_quadratic(vec2f(15.0, -10.0), vec2f(0.0, 0.0));
+        assert_eq!(contour.len(), 12);
+        assert_eq!(contour.endpoint_count(), 5);
+    }
+}
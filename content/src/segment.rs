@@ -10,6 +10,7 @@
 
 //! Single line or Bézier curve segments, optimized with SIMD.
 
+use crate::util::point_is_between;
 use pathfinder_geometry::line_segment::LineSegment2F;
 use pathfinder_geometry::transform2d::Transform2F;
 use pathfinder_geometry::util::EPSILON;
@@ -107,7 +108,9 @@ impl Segment {
         let (p0x, p0y) = (p3p0.z(), p3p0.w());
         let (p1x, p1y) = (4.0 - p0x, (1.0 - p0x) * (3.0 - p0x) / p0y);
         let p2p1 = F32x4::new(p1x, -p1y, p1x, p1y) * F32x4::splat(1.0 / 3.0);
-        return Segment::cubic(LineSegment2F(p3p0), LineSegment2F(p2p1));
+        let mut segment = Segment::cubic(LineSegment2F(p3p0), LineSegment2F(p2p1));
+        segment.flags.insert(SegmentFlags::FROM_ARC);
+        segment
     }
 
     /// Returns a cubic Bézier segment that approximates a quarter of an arc, centered on the +x
@@ -118,7 +121,9 @@ impl Segment {
         let p1 = vec2f(-SQRT_2 / 6.0 + 4.0 / 3.0, 7.0 * SQRT_2 / 6.0 - 4.0 / 3.0);
         let flip = vec2f(1.0, -1.0);
         let (p2, p3) = (p1 * flip, p0 * flip);
-        Segment::cubic(LineSegment2F::new(p3, p0), LineSegment2F::new(p2, p1))
+        let mut segment = Segment::cubic(LineSegment2F::new(p3, p0), LineSegment2F::new(p2, p1));
+        segment.flags.insert(SegmentFlags::FROM_ARC);
+        segment
     }
 
     /// If this segment is a line, returns it. In debug builds, panics otherwise.
@@ -152,6 +157,14 @@ impl Segment {
         self.kind == SegmentKind::Cubic
     }
 
+    /// If this segment is a quadratic Bézier curve, returns it. In debug builds, panics
+    /// otherwise.
+    #[inline]
+    pub fn as_quadratic_segment(&self) -> QuadraticSegment<'_> {
+        debug_assert!(self.is_quadratic());
+        QuadraticSegment(self)
+    }
+
     /// If this segment is a cubic Bézier curve, returns it. In debug builds, panics otherwise.
     #[inline]
     pub fn as_cubic_segment(&self) -> CubicSegment {
@@ -233,16 +246,39 @@ impl Segment {
     }
 
     /// Applies the given affine transform to this segment and returns it.
+    ///
+    /// The control points of lines are unused and are left as they are.
     #[inline]
     pub fn transform(self, transform: &Transform2F) -> Segment {
         Segment {
             baseline: *transform * self.baseline,
-            ctrl: *transform * self.ctrl,
+            ctrl: if self.is_line() { self.ctrl } else { *transform * self.ctrl },
             kind: self.kind,
             flags: self.flags,
         }
     }
 
+    /// Approximates this segment with lines that stay within `tolerance` of it, passing them to
+    /// `f` in order.
+    ///
+    /// Curves are halved until they're flat. Quadratics are first split at their vertical
+    /// extremum, so that the lines reach it exactly.
+    pub fn flatten<F>(&self, tolerance: f32, mut f: F) where F: FnMut(LineSegment2F) {
+        if !self.is_quadratic() {
+            return flatten_line_or_cubic(self, tolerance, &mut f);
+        }
+
+        let quadratic = self.as_quadratic_segment();
+        match quadratic.y_extremum() {
+            None => flatten_quadratic(self, tolerance, &mut f),
+            Some(t) => {
+                let (prev, next) = quadratic.split(t);
+                flatten_quadratic(&prev, tolerance, &mut f);
+                flatten_quadratic(&next, tolerance, &mut f);
+            }
+        }
+    }
+
     pub(crate) fn arc_length(&self) -> f32 {
         // FIXME(pcwalton)
         self.baseline.vector().length()
@@ -254,6 +290,32 @@ impl Segment {
     }
 }
 
+fn flatten_line_or_cubic<F>(segment: &Segment, tolerance: f32, f: &mut F)
+                            where F: FnMut(LineSegment2F) {
+    if segment.is_none() {
+        return;
+    }
+    if segment.is_line() || segment.as_cubic_segment().is_flat(tolerance) {
+        return f(segment.baseline);
+    }
+
+    let (prev, next) = segment.split(0.5);
+    flatten_line_or_cubic(&prev, tolerance, f);
+    flatten_line_or_cubic(&next, tolerance, f);
+}
+
+fn flatten_quadratic<F>(segment: &Segment, tolerance: f32, f: &mut F)
+                        where F: FnMut(LineSegment2F) {
+    let quadratic = segment.as_quadratic_segment();
+    if quadratic.is_flat(tolerance) {
+        return f(segment.baseline);
+    }
+
+    let (prev, next) = quadratic.split(0.5);
+    flatten_quadratic(&prev, tolerance, f);
+    flatten_quadratic(&next, tolerance, f);
+}
+
 /// The type of segment this is.
 #[derive(Clone, Copy, Debug, PartialEq)]
 #[repr(u8)]
@@ -276,6 +338,65 @@ bitflags! {
         /// This segment is the closing segment of the contour (i.e. it returns back to the
         /// starting point).
         const CLOSES_SUBPATH = 0x02;
+        /// This segment is a cubic Bézier curve approximating part of a circular or elliptical
+        /// arc.
+        ///
+        /// Arcs are always converted to cubics, and the conversion can't be undone; this flag
+        /// only records where a curve came from, so that tooling can treat it specially. Arcs
+        /// that were already converted before reaching Pathfinder (for instance, by `usvg`) don't
+        /// carry it.
+        const FROM_ARC = 0x04;
+    }
+}
+
+/// A wrapper for a `Segment` that contains methods specific to quadratic Bézier curves.
+#[derive(Clone, Copy, Debug)]
+pub struct QuadraticSegment<'s>(pub &'s Segment);
+
+impl<'s> QuadraticSegment<'s> {
+    /// Splits this quadratic Bézier curve into two quadratic curves at the given parametric t
+    /// value, which will be clamped to the range 0.0 to 1.0.
+    ///
+    /// Unlike `Segment::split()`, this does not elevate the curve to a cubic first.
+    #[inline]
+    pub fn split(self, t: f32) -> (Segment, Segment) {
+        let t = t.clamp(0.0, 1.0);
+        let (p0, p1, p2) = (self.0.baseline.from(), self.0.ctrl.from(), self.0.baseline.to());
+        let (p01, p12) = (p0.lerp(p1, t), p1.lerp(p2, t));
+        let p012 = p01.lerp(p12, t);
+
+        let mut before = Segment::quadratic(LineSegment2F::new(p0, p012), p01);
+        let mut after = Segment::quadratic(LineSegment2F::new(p012, p2), p12);
+        before.flags = self.0.flags & SegmentFlags::FIRST_IN_SUBPATH;
+        after.flags = self.0.flags & SegmentFlags::CLOSES_SUBPATH;
+        (before, after)
+    }
+
+    /// Returns true if this quadratic Bézier curve is approximately a straight line, within the
+    /// given tolerance.
+    ///
+    /// The curve strays from its baseline by at most a quarter of `p0 - 2 * p1 + p2`.
+    #[inline]
+    pub fn is_flat(self, tolerance: f32) -> bool {
+        let (p0, p1, p2) = (self.0.baseline.from(), self.0.ctrl.from(), self.0.baseline.to());
+        (p0 - p1 * 2.0 + p2).square_length() <= 16.0 * tolerance * tolerance
+    }
+
+    /// Returns the parametric t value of the point at which this curve changes vertical
+    /// direction, if it does so strictly between its endpoints.
+    ///
+    /// A quadratic curve has at most one such point, at
+    /// `t = (p0.y - p1.y) / (p0.y - 2 * p1.y + p2.y)`.
+    #[inline]
+    pub fn y_extremum(self) -> Option<f32> {
+        let (p0y, p1y) = (self.0.baseline.from_y(), self.0.ctrl.from_y());
+        let p2y = self.0.baseline.to_y();
+        let denominator = p0y - 2.0 * p1y + p2y;
+        if denominator.abs() < EPSILON {
+            return None;
+        }
+        let t = (p0y - p1y) / denominator;
+        if t > 0.0 && t < 1.0 { Some(t) } else { None }
     }
 }
 
@@ -296,7 +417,16 @@ impl<'s> CubicSegment<'s> {
             - self.0.baseline.reversed().0;
         uv = uv * uv;
         uv = uv.max(uv.zwxy());
-        uv[0] + uv[1] <= 16.0 * tolerance * tolerance
+        if uv[0] + uv[1] <= 16.0 * tolerance * tolerance {
+            return true;
+        }
+
+        // The above bound assumes evenly spaced control points, so it rejects straight curves
+        // whose control points bunch up near one end. Such a curve stays within its convex hull,
+        // so if both control points lie within `tolerance` of the baseline, so does the curve.
+        let (from, to) = (self.0.baseline.from(), self.0.baseline.to());
+        point_is_between(from, self.0.ctrl.from(), to, tolerance) &&
+            point_is_between(from, self.0.ctrl.to(), to, tolerance)
     }
 
     /// Splits this cubic Bézier curve into two at the given parametric t value, which will be
@@ -347,13 +477,13 @@ impl<'s> CubicSegment<'s> {
                 baseline: baseline0,
                 ctrl: ctrl0,
                 kind: SegmentKind::Cubic,
-                flags: self.0.flags & SegmentFlags::FIRST_IN_SUBPATH,
+                flags: self.0.flags & (SegmentFlags::FIRST_IN_SUBPATH | SegmentFlags::FROM_ARC),
             },
             Segment {
                 baseline: baseline1,
                 ctrl: ctrl1,
                 kind: SegmentKind::Cubic,
-                flags: self.0.flags & SegmentFlags::CLOSES_SUBPATH,
+                flags: self.0.flags & (SegmentFlags::CLOSES_SUBPATH | SegmentFlags::FROM_ARC),
             },
         )
     }
@@ -400,3 +530,108 @@ impl<'s> CubicSegment<'s> {
         f32::max(self.0.baseline.max_y(), self.0.ctrl.max_y())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::outline::{Contour, Outline};
+    use crate::segment::Segment;
+    use pathfinder_geometry::line_segment::LineSegment2F;
+    use pathfinder_geometry::transform2d::Transform2F;
+    use pathfinder_geometry::vector::vec2f;
+
+    #[test]
+    fn test_quadratic_split_at_y_extremum() {
+        let quadratic = Segment::quadratic(LineSegment2F::new(vec2f(0.0, 0.0), vec2f(8.0, 3.0)),
+                                           vec2f(2.0, 6.0));
+        let t = quadratic.as_quadratic_segment().y_extremum().unwrap();
+        assert!((t - 2.0 / 3.0).abs() < 0.00001);
+
+        let (before, after) = quadratic.as_quadratic_segment().split(t);
+        assert!(before.is_quadratic() && after.is_quadratic());
+        assert_eq!(before.baseline.to(), after.baseline.from());
+
+        // The peak of the curve is at (40/9, 4). Both halves have a horizontal tangent there, so
+        // each control point is level with the peak and both halves are y-monotonic.
+        let peak = before.baseline.to();
+        assert!((peak - vec2f(40.0 / 9.0, 4.0)).length() < 0.00001);
+        assert!((before.ctrl.from_y() - peak.y()).abs() < 0.00001);
+        assert!((after.ctrl.from_y() - peak.y()).abs() < 0.00001);
+    }
+
+    #[test]
+    fn test_flattened_quadratic_reaches_y_extremum() {
+        let quadratic = Segment::quadratic(LineSegment2F::new(vec2f(0.0, 0.0), vec2f(8.0, 3.0)),
+                                           vec2f(2.0, 6.0));
+        let mut lines = vec![];
+        quadratic.flatten(0.01, |line| lines.push(line));
+        assert!(lines.len() > 2);
+        assert_eq!(lines[0].from(), quadratic.baseline.from());
+        assert_eq!(lines[lines.len() - 1].to(), quadratic.baseline.to());
+        assert!(lines.windows(2).all(|pair| pair[0].to() == pair[1].from()));
+
+        // One of the lines ends exactly at the peak.
+        let peak_y = lines.iter().map(|line| line.to_y()).fold(0.0, f32::max);
+        assert!((peak_y - 4.0).abs() < 0.00001);
+    }
+
+    #[test]
+    fn test_quadratic_flatness() {
+        // The curve's midpoint strays 1.0 from the baseline.
+        let quadratic = Segment::quadratic(LineSegment2F::new(vec2f(0.0, 0.0), vec2f(8.0, 0.0)),
+                                           vec2f(4.0, 2.0));
+        assert!(quadratic.as_quadratic_segment().is_flat(1.0));
+        assert!(!quadratic.as_quadratic_segment().is_flat(0.99));
+    }
+
+    #[test]
+    fn test_nearly_straight_cubic_flattens_to_one_line() {
+        // The control points sit almost on the baseline but crowd its ends.
+        let cubic = Segment::cubic(LineSegment2F::new(vec2f(0.0, 0.0), vec2f(100.0, 0.0)),
+                                   LineSegment2F::new(vec2f(1.0, 0.01), vec2f(99.5, -0.01)));
+        assert!(cubic.as_cubic_segment().is_flat(0.25));
+
+        let mut contour = Contour::new();
+        contour.push_endpoint(cubic.baseline.from());
+        contour.push_cubic(cubic.ctrl.from(), cubic.ctrl.to(), cubic.baseline.to());
+        let mut outline = Outline::new();
+        outline.push_contour(contour);
+        assert_eq!(outline.flattened(0.25).contours()[0].len(), 2);
+
+        // A curve that actually bends still gets subdivided.
+        let curve = Segment::cubic(LineSegment2F::new(vec2f(0.0, 0.0), vec2f(100.0, 0.0)),
+                                   LineSegment2F::new(vec2f(1.0, 10.0), vec2f(99.5, 10.0)));
+        assert!(!curve.as_cubic_segment().is_flat(0.25));
+    }
+
+    #[test]
+    fn test_transform_matches_transforming_each_point() {
+        let cubic = Segment::cubic(LineSegment2F::new(vec2f(1.0, 2.0), vec2f(9.0, -3.0)),
+                                   LineSegment2F::new(vec2f(-4.0, 7.5), vec2f(6.25, 0.5)));
+        let transform = Transform2F::row_major(2.0, -0.5, 3.0, 0.75, 1.5, -8.0);
+        let transformed = cubic.transform(&transform);
+        assert!(transformed.is_cubic());
+        assert_eq!(transformed.baseline.from(), transform * cubic.baseline.from());
+        assert_eq!(transformed.baseline.to(), transform * cubic.baseline.to());
+        assert_eq!(transformed.ctrl.from(), transform * cubic.ctrl.from());
+        assert_eq!(transformed.ctrl.to(), transform * cubic.ctrl.to());
+    }
+
+    #[test]
+    fn test_cubic_split_matches_scalar_de_casteljau() {
+        let cubic = Segment::cubic(LineSegment2F::new(vec2f(1.0, 2.0), vec2f(9.0, -3.0)),
+                                   LineSegment2F::new(vec2f(-4.0, 7.5), vec2f(6.25, 0.5)));
+        let (p0, p1) = (cubic.baseline.from(), cubic.ctrl.from());
+        let (p2, p3) = (cubic.ctrl.to(), cubic.baseline.to());
+        for &t in &[0.125, 0.25, 1.0 / 3.0, 0.5, 0.7, 0.999] {
+            let (p01, p12, p23) = (p0.lerp(p1, t), p1.lerp(p2, t), p2.lerp(p3, t));
+            let (p012, p123) = (p01.lerp(p12, t), p12.lerp(p23, t));
+            let p0123 = p012.lerp(p123, t);
+
+            let (before, after) = cubic.as_cubic_segment().split(t);
+            assert_eq!(before.baseline, LineSegment2F::new(p0, p0123));
+            assert_eq!(before.ctrl, LineSegment2F::new(p01, p012));
+            assert_eq!(after.baseline, LineSegment2F::new(p0123, p3));
+            assert_eq!(after.ctrl, LineSegment2F::new(p123, p23));
+        }
+    }
+}
@@ -70,7 +70,10 @@ pub enum LineCap {
 pub enum LineJoin {
     /// Connected segments are joined by extending their outside edges to connect at a single
     /// point, with the effect of filling an additional lozenge-shaped area. The `f32` value
-    /// specifies the miter limit ratio.
+    /// specifies the miter limit ratio: if the ratio of the miter length to the line width would
+    /// exceed it, a bevel join is used instead.
+    ///
+    /// The default is 10, as in HTML canvas. Note that SVG's default is 4.
     Miter(f32),
     /// Fills an additional triangular area between the common endpoint of connected segments and
     /// the separate outside rectangular corners of each segment.
@@ -93,6 +96,13 @@ impl<'a> OutlineStrokeToFill<'a> {
     pub fn offset(&mut self) {
         let mut new_contours = vec![];
         for input in &self.input.contours {
+            if let Some(position) = degenerate_contour_position(input) {
+                if let Some(dot) = self.dot(position) {
+                    new_contours.push(dot);
+                }
+                continue;
+            }
+
             let closed = input.closed;
             let mut stroker = ContourStrokeToFill::new(input,
                                                        Contour::new(),
@@ -149,6 +159,27 @@ impl<'a> OutlineStrokeToFill<'a> {
         new_contours.push(stroker.output);
     }
 
+    // Zero-length subpaths are drawn as a dot of the cap shape, as SVG specifies.
+    fn dot(&self, position: Vector2F) -> Option<Contour> {
+        let radius = self.style.line_width * 0.5;
+        let mut contour = Contour::new();
+        match self.style.line_cap {
+            LineCap::Butt => return None,
+            LineCap::Square => {
+                contour.push_endpoint(position + vec2f(-radius, -radius));
+                contour.push_endpoint(position + vec2f( radius, -radius));
+                contour.push_endpoint(position + vec2f( radius,  radius));
+                contour.push_endpoint(position + vec2f(-radius,  radius));
+            }
+            LineCap::Round => {
+                let transform = Transform2F::from_scale(radius).translate(position);
+                contour.push_ellipse(&transform);
+            }
+        }
+        contour.closed = true;
+        Some(contour)
+    }
+
     fn add_cap(&mut self, contour: &mut Contour) {
         if self.style.line_cap == LineCap::Butt || contour.len() < 2 {
             return
@@ -199,6 +230,19 @@ impl<'a> OutlineStrokeToFill<'a> {
     }
 }
 
+// Returns the position of the contour if all of its points coincide.
+fn degenerate_contour_position(contour: &Contour) -> Option<Vector2F> {
+    if contour.is_empty() {
+        return None;
+    }
+    let position = contour.position_of(0);
+    if contour.points().iter().all(|&point| (point - position).square_length() <= EPSILON) {
+        Some(position)
+    } else {
+        None
+    }
+}
+
 struct ContourStrokeToFill<'a> {
     input: &'a Contour,
     output: Contour,
@@ -463,3 +507,141 @@ impl Default for LineJoin {
     #[inline]
     fn default() -> LineJoin { LineJoin::Miter(10.0) }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::outline::{Contour, Outline};
+    use crate::stroke::{LineCap, LineJoin, OutlineStrokeToFill, StrokeStyle};
+    use pathfinder_geometry::rect::RectF;
+    use pathfinder_geometry::vector::{Vector2F, vec2f};
+
+    fn stroke_polyline(points: &[Vector2F], line_join: LineJoin) -> Outline {
+        let mut contour = Contour::new();
+        points.iter().for_each(|&point| contour.push_endpoint(point));
+        let mut outline = Outline::new();
+        outline.push_contour(contour);
+
+        let style = StrokeStyle { line_width: 2.0, line_cap: LineCap::Butt, line_join };
+        let mut stroke_to_fill = OutlineStrokeToFill::new(&outline, style);
+        stroke_to_fill.offset();
+        stroke_to_fill.into_outline()
+    }
+
+    fn has_point_near(outline: &Outline, point: Vector2F) -> bool {
+        outline.contours().iter().any(|contour| {
+            contour.points().iter().any(|&p| (p - point).length() < 0.01)
+        })
+    }
+
+    #[test]
+    fn test_zero_length_subpath_with_round_caps_is_a_dot() {
+        let mut contour = Contour::new();
+        contour.push_endpoint(vec2f(10.0, 10.0));
+        let mut outline = Outline::new();
+        outline.push_contour(contour);
+
+        let style = StrokeStyle { line_width: 4.0, line_cap: LineCap::Round, ..Default::default() };
+        let mut stroke_to_fill = OutlineStrokeToFill::new(&outline, style);
+        stroke_to_fill.offset();
+        let dot = stroke_to_fill.into_outline();
+
+        assert_eq!(dot.contours().len(), 1);
+        let contour = &dot.contours()[0];
+        assert!(contour.is_closed());
+        for point_index in 0..contour.len() {
+            if contour.point_is_endpoint(point_index) {
+                let distance = (contour.position_of(point_index) - vec2f(10.0, 10.0)).length();
+                assert!((distance - 2.0).abs() < 0.001);
+            }
+        }
+        // Bounds include control points, so they are a little larger than the circle.
+        let bounds = dot.bounds();
+        assert!(bounds.contains_rect(RectF::new(vec2f(8.0, 8.0), vec2f(4.0, 4.0))));
+        assert!(RectF::new(vec2f(7.5, 7.5), vec2f(5.0, 5.0)).contains_rect(bounds));
+
+        // Butt caps have no extent along a zero-length path, so nothing is drawn.
+        let style = StrokeStyle { line_width: 4.0, line_cap: LineCap::Butt, ..Default::default() };
+        let mut stroke_to_fill = OutlineStrokeToFill::new(&outline, style);
+        stroke_to_fill.offset();
+        assert!(stroke_to_fill.into_outline().contours().is_empty());
+    }
+
+    #[test]
+    fn test_line_caps_extend_open_lines_only() {
+        fn stroked_bounds(closed: bool, line_cap: LineCap) -> RectF {
+            let mut contour = Contour::new();
+            contour.push_endpoint(vec2f(10.0, 20.0));
+            contour.push_endpoint(vec2f(50.0, 20.0));
+            if closed {
+                contour.push_endpoint(vec2f(50.0, 40.0));
+                contour.close();
+            }
+            let mut outline = Outline::new();
+            outline.push_contour(contour);
+
+            let style = StrokeStyle { line_width: 4.0, line_cap, ..Default::default() };
+            let mut stroke_to_fill = OutlineStrokeToFill::new(&outline, style);
+            stroke_to_fill.offset();
+            stroke_to_fill.into_outline().bounds()
+        }
+
+        // Square caps extend each end by half the width, for the full width in total.
+        let butt = stroked_bounds(false, LineCap::Butt);
+        assert_eq!(butt, RectF::new(vec2f(10.0, 18.0), vec2f(40.0, 4.0)));
+        let square = stroked_bounds(false, LineCap::Square);
+        assert_eq!(square, RectF::new(vec2f(8.0, 18.0), vec2f(44.0, 4.0)));
+
+        // Bounds include control points, so round caps reach a little past the square ones.
+        let round = stroked_bounds(false, LineCap::Round);
+        assert!(round.contains_rect(RectF::new(vec2f(8.0, 18.0), vec2f(44.0, 4.0))));
+        assert!(RectF::new(vec2f(7.5, 17.5), vec2f(45.0, 5.0)).contains_rect(round));
+
+        // Closed contours have no ends to cap.
+        assert_eq!(stroked_bounds(true, LineCap::Square), stroked_bounds(true, LineCap::Butt));
+        assert_eq!(stroked_bounds(true, LineCap::Round), stroked_bounds(true, LineCap::Butt));
+    }
+
+    #[test]
+    fn test_sharp_miter_exceeding_limit_becomes_bevel() {
+        // The angle here is about 5.7°, so the miter ratio is about 20.
+        let points = [vec2f(0.0, 0.0), vec2f(100.0, 0.0), vec2f(0.0, 10.0)];
+
+        let beveled = stroke_polyline(&points, LineJoin::Miter(4.0));
+        assert!(beveled.bounds().max_x() < 101.5);
+
+        let mitered = stroke_polyline(&points, LineJoin::Miter(40.0));
+        assert!(mitered.bounds().max_x() > 110.0);
+    }
+
+    #[test]
+    fn test_shallow_miter_within_limit_is_kept() {
+        // A right angle has a miter ratio of √2.
+        let points = [vec2f(0.0, 0.0), vec2f(100.0, 0.0), vec2f(100.0, 100.0)];
+
+        let mitered = stroke_polyline(&points, LineJoin::Miter(4.0));
+        assert!(has_point_near(&mitered, vec2f(101.0, -1.0)));
+
+        let beveled = stroke_polyline(&points, LineJoin::Miter(1.0));
+        assert!(!has_point_near(&beveled, vec2f(101.0, -1.0)));
+    }
+
+    #[test]
+    fn test_right_angle_join_point_counts() {
+        let points = [vec2f(0.0, 0.0), vec2f(100.0, 0.0), vec2f(100.0, 100.0)];
+        let counts = |line_join| {
+            let outline = stroke_polyline(&points, line_join);
+            assert_eq!(outline.contours().len(), 1);
+            let contour = &outline.contours()[0];
+            (contour.len(), contour.endpoint_count())
+        };
+
+        // Each side of the stroke gets a join at the corner. The inner one is covered by the
+        // stroke itself. A miter adds its tip to each join, and a round join adds arcs, which
+        // have control points.
+        assert_eq!(counts(LineJoin::Bevel), (8, 8));
+        assert_eq!(counts(LineJoin::Miter(4.0)), (10, 10));
+        let (round_len, round_endpoint_count) = counts(LineJoin::Round);
+        assert!(round_endpoint_count > 8);
+        assert!(round_len > round_endpoint_count);
+    }
+}
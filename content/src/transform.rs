@@ -33,19 +33,11 @@ where
 
     #[inline]
     fn next(&mut self) -> Option<Segment> {
-        // TODO(pcwalton): Can we go faster by transforming an entire line segment with SIMD?
-        let mut segment = self.iter.next()?;
-        if !segment.is_none() {
-            segment.baseline.set_from(self.transform * segment.baseline.from());
-            segment.baseline.set_to(self.transform * segment.baseline.to());
-            if !segment.is_line() {
-                segment.ctrl.set_from(self.transform * segment.ctrl.from());
-                if !segment.is_quadratic() {
-                    segment.ctrl.set_to(self.transform * segment.ctrl.to());
-                }
-            }
+        let segment = self.iter.next()?;
+        if segment.is_none() {
+            return Some(segment);
         }
-        Some(segment)
+        Some(segment.transform(&self.transform))
     }
 }
 
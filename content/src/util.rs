@@ -12,6 +12,7 @@
 
 use pathfinder_geometry::line_segment::LineSegment2F;
 use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::vector::Vector2F;
 use pathfinder_simd::default::{F32x2, F32x4};
 use std::hash::{Hash, Hasher};
 use std::mem;
@@ -54,3 +55,16 @@ pub(crate) fn safe_sqrt(x: f32) -> f32 {
         x.sqrt()
     }
 }
+
+// Returns true if `point` lies within `epsilon` of the line from `from` to `to` and projects onto
+// it between the two endpoints.
+pub(crate) fn point_is_between(from: Vector2F, point: Vector2F, to: Vector2F, epsilon: f32)
+                               -> bool {
+    let (vector, offset) = (to - from, point - from);
+    let length = vector.length();
+    if length == 0.0 {
+        return offset.length() <= epsilon;
+    }
+    let t = vector.projection_coefficient(offset);
+    (0.0..=1.0).contains(&t) && vector.det(offset).abs() / length <= epsilon
+}
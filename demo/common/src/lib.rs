@@ -34,15 +34,16 @@ use pathfinder_geometry::transform2d::Transform2F;
 use pathfinder_geometry::transform3d::Transform4F;
 use pathfinder_geometry::vector::{Vector2F, Vector2I, Vector4F, vec2f, vec2i};
 use pathfinder_gpu::Device;
+use pathfinder_renderer::concurrent::executor::SequentialExecutor;
 use pathfinder_renderer::concurrent::scene_proxy::SceneProxy;
 use pathfinder_renderer::gpu::options::{DestFramebuffer, RendererLevel};
 use pathfinder_renderer::gpu::options::{RendererMode, RendererOptions};
 use pathfinder_renderer::gpu::renderer::{DebugUIPresenterInfo, Renderer};
-use pathfinder_renderer::options::{BuildOptions, RenderTransform};
+use pathfinder_renderer::options::{BuildOptions, DEFAULT_FLATTENING_TOLERANCE, RenderTransform};
 use pathfinder_renderer::paint::Paint;
 use pathfinder_renderer::scene::{DrawPath, RenderTarget, Scene};
 use pathfinder_resources::ResourceLoader;
-use pathfinder_svg::SVGScene;
+use pathfinder_svg::{DEFAULT_MIN_STROKE_WIDTH, SVGBuildOptions, SVGScene};
 use pathfinder_ui::{MousePosition, UIEvent};
 use std::fs::File;
 use std::io::BufWriter;
@@ -75,6 +76,9 @@ const APPROX_FONT_SIZE: f32 = 16.0;
 
 const MESSAGE_TIMEOUT_SECS: u64 = 5;
 
+// The width of the strokes drawn in wireframe mode, in scene units.
+const WIREFRAME_LINE_WIDTH: f32 = 0.5;
+
 pub mod window;
 
 mod camera;
@@ -108,6 +112,8 @@ pub struct DemoApp<W> where W: Window {
     pending_screenshot_info: Option<ScreenshotInfo>,
     mouselook_enabled: bool,
     pub dirty: bool,
+    // Whether the next build should also validate the scene, because it changed.
+    validation_pending: bool,
     expire_message_event_id: u32,
     message_epoch: u32,
     last_mouse_position: Vector2I,
@@ -166,9 +172,9 @@ impl<W> DemoApp<W> where W: Window {
         let filter = build_filter(&ui_model);
 
         let viewport = window.viewport(options.mode.view(0));
-        let mut content = load_scene(resources, &options.input_path);
+        let mut content = load_scene(resources, &options.input_path, &options.usvg_options);
 
-        let (mut scene, message) = content.render(viewport.size(), filter);
+        let (mut scene, message) = content.render(viewport.size(), filter, &options);
 
         let renderer = Renderer::new(device, resources, render_mode, render_options);
 
@@ -176,6 +182,7 @@ impl<W> DemoApp<W> where W: Window {
                                                                   viewport.size());
         let camera = Camera::new(options.mode, scene_metadata.view_box, viewport.size());
 
+        let validation_pending = options.validate;
         let scene_proxy = SceneProxy::from_scene(scene, level, executor);
 
         let ground_program = GroundProgram::new(renderer.device(), resources);
@@ -210,6 +217,7 @@ impl<W> DemoApp<W> where W: Window {
             pending_screenshot_info: None,
             mouselook_enabled: false,
             dirty: true,
+            validation_pending,
             expire_message_event_id,
             message_epoch,
             last_mouse_position: Vector2I::default(),
@@ -277,8 +285,16 @@ impl<W> DemoApp<W> where W: Window {
                 Vector2F::zero()
             },
             subpixel_aa_enabled: self.ui_model.subpixel_aa_effect_enabled,
+            snap_to_tiles: self.options.snap_to_tiles,
+            flattening_tolerance: self.options.flattening_tolerance,
+            ..BuildOptions::default()
         };
 
+        if self.validation_pending {
+            self.validate_scene(build_options.clone());
+            self.validation_pending = false;
+        }
+
         self.scene_proxy.build(build_options);
         /*
         self.render_command_stream =    
@@ -286,6 +302,19 @@ impl<W> DemoApp<W> where W: Window {
             */
     }
 
+    // Builds a copy of the scene with the options it's about to be rendered with, and reports any
+    // invariant the render commands violate.
+    fn validate_scene(&mut self, build_options: BuildOptions) {
+        let mut scene = self.scene_proxy.copy_scene();
+        if let Err(error) = scene.build_and_validate(build_options, &SequentialExecutor) {
+            error!("Scene failed validation: {}", error);
+            emit_message::<W>(&mut self.ui_model,
+                              &mut self.message_epoch,
+                              self.expire_message_event_id,
+                              format!("Scene failed validation: {}", error));
+        }
+    }
+
     fn handle_events(&mut self, events: Vec<Event>) -> Vec<UIEvent> {
         let mut ui_events = vec![];
         self.dirty = false;
@@ -444,9 +473,13 @@ impl<W> DemoApp<W> where W: Window {
                 Event::OpenData(ref data_path) => {
                     let viewport = self.window.viewport(self.ui_model.mode.view(0));
                     let filter = build_filter(&self.ui_model);
-                    self.content = load_scene(self.window.resource_loader(), data_path);
+                    self.content = load_scene(self.window.resource_loader(),
+                                              data_path,
+                                              &self.options.usvg_options);
 
-                    let (mut scene, message) = self.content.render(viewport.size(), filter);
+                    let (mut scene, message) = self.content.render(viewport.size(),
+                                                                   filter,
+                                                                   &self.options);
                     self.ui_model.message = message;
 
                     let viewport_size = self.window.viewport(self.ui_model.mode.view(0)).size();
@@ -456,6 +489,7 @@ impl<W> DemoApp<W> where W: Window {
                                               self.scene_metadata.view_box,
                                               viewport_size);
 
+                    self.validation_pending = self.options.validate;
                     self.scene_proxy.replace_scene(scene);
 
                     self.dirty = true;
@@ -578,9 +612,10 @@ impl<W> DemoApp<W> where W: Window {
             UIAction::EffectsChanged => {
                 let viewport_size = self.window.viewport(self.ui_model.mode.view(0)).size();
                 let filter = build_filter(&self.ui_model);
-                let (mut scene, _) = self.content.render(viewport_size, filter);
+                let (mut scene, _) = self.content.render(viewport_size, filter, &self.options);
                 self.scene_metadata =
                     SceneMetadata::new_clipping_view_box(&mut scene, viewport_size);
+                self.validation_pending = self.options.validate;
                 self.scene_proxy.replace_scene(scene);
                 self.dirty = true;
             }
@@ -632,6 +667,14 @@ pub struct Options {
     pub background_color: BackgroundColor,
     pub high_performance_gpu: bool,
     pub renderer_level: Option<RendererLevel>,
+    pub wireframe: bool,
+    pub flip_y: bool,
+    pub usvg_options: UsvgOptions,
+    pub min_stroke_width: f32,
+    pub validate: bool,
+    pub snap_to_tiles: bool,
+    pub flattening_tolerance: f32,
+    pub fit_content: Option<f32>,
     hidden_field_for_future_proofing: (),
 }
 
@@ -645,6 +688,14 @@ impl Default for Options {
             background_color: BackgroundColor::Light,
             high_performance_gpu: false,
             renderer_level: None,
+            wireframe: false,
+            flip_y: false,
+            usvg_options: UsvgOptions::default(),
+            min_stroke_width: DEFAULT_MIN_STROKE_WIDTH,
+            validate: false,
+            snap_to_tiles: false,
+            flattening_tolerance: DEFAULT_FLATTENING_TOLERANCE,
+            fit_content: None,
             hidden_field_for_future_proofing: (),
         }
     }
@@ -705,6 +756,63 @@ impl Options {
                     .takes_value(true)
                     .possible_values(&["9", "11"])
             )
+            .arg(
+                Arg::with_name("wireframe")
+                    .short("w")
+                    .long("wireframe")
+                    .help("Draw the edges of paths instead of filling them")
+            )
+            .arg(
+                Arg::with_name("flip-y")
+                    .long("flip-y")
+                    .help("Flip SVG content vertically for y-up output")
+            )
+            .arg(
+                Arg::with_name("validate")
+                    .long("validate")
+                    .help("Check built scenes against tiler invariants, reporting violations")
+            )
+            .arg(
+                Arg::with_name("snap-to-tiles")
+                    .long("snap-to-tiles")
+                    .help("Round all geometry to tile corners, trading detail for fewer masks")
+            )
+            .arg(
+                Arg::with_name("fit-content")
+                    .long("fit-content")
+                    .value_name("PADDING")
+                    .takes_value(true)
+                    .min_values(0)
+                    .help("Show all content, even outside the view box, with optional padding")
+            )
+            .arg(
+                Arg::with_name("tolerance")
+                    .long("tolerance")
+                    .value_name("PIXELS")
+                    .takes_value(true)
+                    .help("How far flattened curves may stray from the true curves")
+            )
+            .arg(
+                Arg::with_name("dpi")
+                    .long("dpi")
+                    .value_name("DPI")
+                    .takes_value(true)
+                    .help("Resolution used to resolve absolute units in SVG content")
+            )
+            .arg(
+                Arg::with_name("font-family")
+                    .long("font-family")
+                    .value_name("FAMILY")
+                    .takes_value(true)
+                    .help("Font family to use for SVG text that doesn't specify one")
+            )
+            .arg(
+                Arg::with_name("min-stroke-width")
+                    .long("min-stroke-width")
+                    .value_name("WIDTH")
+                    .takes_value(true)
+                    .help("Width that thinner SVG strokes are widened to")
+            )
             .arg(
                 Arg::with_name("INPUT")
                     .help("Path to the SVG file to render")
@@ -750,6 +858,49 @@ impl Options {
             }
         }
 
+        if matches.is_present("wireframe") {
+            self.wireframe = true;
+        }
+
+        if matches.is_present("flip-y") {
+            self.flip_y = true;
+        }
+
+        if matches.is_present("validate") {
+            self.validate = true;
+        }
+
+        if matches.is_present("snap-to-tiles") {
+            self.snap_to_tiles = true;
+        }
+
+        if matches.is_present("fit-content") {
+            let padding = matches.value_of("fit-content").and_then(|padding| padding.parse().ok());
+            self.fit_content = Some(padding.unwrap_or(0.0));
+        }
+
+        if let Some(dpi) = matches.value_of("dpi") {
+            if let Ok(dpi) = dpi.parse() {
+                self.usvg_options.dpi = dpi;
+            }
+        }
+
+        if let Some(font_family) = matches.value_of("font-family") {
+            self.usvg_options.font_family = font_family.to_owned();
+        }
+
+        if let Some(tolerance) = matches.value_of("tolerance") {
+            if let Ok(tolerance) = tolerance.parse() {
+                self.flattening_tolerance = tolerance;
+            }
+        }
+
+        if let Some(min_stroke_width) = matches.value_of("min-stroke-width") {
+            if let Ok(min_stroke_width) = min_stroke_width.parse() {
+                self.min_stroke_width = min_stroke_width;
+            }
+        }
+
         if let Some(path) = matches.value_of("INPUT") {
             self.input_path = DataPath::Path(PathBuf::from(path));
         };
@@ -764,10 +915,17 @@ pub enum UIVisibility {
 }
 
 impl Content {
-    fn render(&mut self, viewport_size: Vector2I, filter: Option<PatternFilter>) -> (Scene, String) {
-        match *self {
+    fn render(&mut self,
+              viewport_size: Vector2I,
+              filter: Option<PatternFilter>,
+              options: &Options)
+              -> (Scene, String) {
+        let (mut scene, message) = match *self {
             Content::Svg(ref tree) => {
-                let built_svg = build_svg_tree(&tree, viewport_size, filter);
+                let built_svg = build_svg_tree(&tree, viewport_size, filter, options);
+                for warning in &built_svg.warnings {
+                    warn!("Unsupported SVG feature: {}", warning);
+                }
                 let message = get_svg_building_message(&built_svg);
                 (built_svg.scene, message)
             }
@@ -777,12 +935,24 @@ impl Content {
                 pdf_render::render_page(&mut backend, &file.resolver(), &page, Transform2F::default()).unwrap();
                 (backend.finish(), String::new())
             }
+        };
+
+        if options.wireframe {
+            scene.convert_to_wireframe(WIREFRAME_LINE_WIDTH);
         }
+
+        if let Some(padding) = options.fit_content {
+            let content_bounds = scene.content_bounds();
+            scene.set_view_box(content_bounds.dilate(padding));
+        }
+
+        (scene, message)
     }
 }
 
 fn load_scene(resource_loader: &dyn ResourceLoader,
-              input_path: &DataPath,)
+              input_path: &DataPath,
+              usvg_options: &UsvgOptions)
               -> Content {
     let data = match *input_path {
         DataPath::Default => resource_loader.slurp(DEFAULT_SVG_VIRTUAL_PATH).unwrap(),
@@ -790,7 +960,7 @@ fn load_scene(resource_loader: &dyn ResourceLoader,
         DataPath::Path(ref path) => std::fs::read(path).unwrap().into()
     };
 
-    if let Ok(tree) = SvgTree::from_data(&data, &UsvgOptions::default()) {
+    if let Ok(tree) = SvgTree::from_data(&data, usvg_options) {
         Content::Svg(tree)
     } else if let Ok(file) = FileOptions::cached().load(data) {
         Content::Pdf { file, cache: PdfRenderCache::new(), page_nr: 0 }
@@ -801,7 +971,11 @@ fn load_scene(resource_loader: &dyn ResourceLoader,
 
 // FIXME(pcwalton): Rework how transforms work in the demo. The transform affects the final
 // composite steps, breaking this approach.
-fn build_svg_tree(tree: &SvgTree, viewport_size: Vector2I, filter: Option<PatternFilter>) -> SVGScene {
+fn build_svg_tree(tree: &SvgTree,
+                  viewport_size: Vector2I,
+                  filter: Option<PatternFilter>,
+                  options: &Options)
+                  -> SVGScene {
     let mut scene = Scene::new();
     let filter_info = filter.map(|filter| {
         let scale = match filter {
@@ -815,7 +989,18 @@ fn build_svg_tree(tree: &SvgTree, viewport_size: Vector2I, filter: Option<Patter
         FilterInfo { filter, render_target_id, render_target_size }
     });
 
-    let mut built_svg = SVGScene::from_tree_and_scene(&tree, scene);
+    let transform = if options.flip_y {
+        SVGScene::flip_y_transform(&tree)
+    } else {
+        Transform2F::default()
+    };
+    let svg_build_options = SVGBuildOptions {
+        transform,
+        min_stroke_width: options.min_stroke_width,
+        ..SVGBuildOptions::default()
+    };
+    let mut built_svg =
+        SVGScene::from_tree_and_scene_with_options(&tree, scene, &svg_build_options);
     if let Some(FilterInfo { filter, render_target_id, render_target_size }) = filter_info {
         let mut pattern = Pattern::from_render_target(render_target_id, render_target_size);
         pattern.set_filter(Some(filter));
@@ -931,3 +1116,41 @@ fn build_filter(ui_model: &DemoUIModel) -> Option<PatternFilter> {
         }
     })
 }
+
+#[cfg(test)]
+mod test {
+    use crate::window::DataPath;
+    use crate::{Content, load_scene};
+    use pathfinder_resources::ResourceLoader;
+    use std::io::Error as IOError;
+    use usvg::Options as UsvgOptions;
+
+    struct StringResourceLoader(&'static str);
+
+    impl ResourceLoader for StringResourceLoader {
+        fn slurp(&self, _: &str) -> Result<Vec<u8>, IOError> {
+            Ok(self.0.as_bytes().to_vec())
+        }
+    }
+
+    #[test]
+    fn test_dpi_changes_resolved_size() {
+        let loader = StringResourceLoader(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="1in" height="0.5in">
+                <rect width="100%" height="100%"/>
+            </svg>"#);
+        let size_at_dpi = |dpi| {
+            let usvg_options = UsvgOptions { dpi, ..UsvgOptions::default() };
+            match load_scene(&loader, &DataPath::Default, &usvg_options) {
+                Content::Svg(tree) => {
+                    let size = tree.svg_node().size;
+                    (size.width(), size.height())
+                }
+                Content::Pdf { .. } => panic!("expected SVG content"),
+            }
+        };
+
+        assert_eq!(size_at_dpi(96.0), (96.0, 48.0));
+        assert_eq!(size_at_dpi(192.0), (192.0, 96.0));
+    }
+}
@@ -27,6 +27,7 @@ use pathfinder_resources::fs::FilesystemResourceLoader;
 use std::cell::Cell;
 use std::collections::VecDeque;
 use std::path::PathBuf;
+use std::process;
 use std::sync::Mutex;
 use surfman::{SurfaceAccess, SurfaceType, declare_surfman};
 use winit::{ControlFlow, ElementState, Event as WinitEvent, EventsLoop, EventsLoopProxy};
@@ -71,6 +72,12 @@ fn main() {
     color_backtrace::install();
     pretty_env_logger::init();
 
+    // Fail cleanly rather than with an illegal instruction somewhere down the line.
+    if let Err(error) = pathfinder_simd::check_cpu_features() {
+        eprintln!("error: {}; rebuild with `--features pf-no-simd` to run here", error);
+        process::exit(1);
+    }
+
     // Read command line options.
     let mut options = Options::default();
     options.command_line_overrides();
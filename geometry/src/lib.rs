@@ -9,9 +9,19 @@
 // except according to those terms.
 
 //! Basic geometry and linear algebra primitives, optimized with SIMD.
+//!
+//! The `std` feature is on by default. Without it, this crate is `no_std` and needs the `libm`
+//! feature for the floating-point functions that `core` lacks. The `pf-no-simd` feature swaps the
+//! SIMD code for portable scalar fallbacks.
+
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+#[cfg(not(any(feature = "std", feature = "libm")))]
+compile_error!("pathfinder_geometry needs either the `std` or the `libm` feature");
 
 pub mod angle;
 pub mod line_segment;
+mod math;
 pub mod rect;
 pub mod transform2d;
 pub mod transform3d;
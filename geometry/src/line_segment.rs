@@ -10,13 +10,15 @@
 
 //! Line segment types, optimized with SIMD.
 
+use crate::math;
 use crate::transform2d::Matrix2x2F;
 use crate::util;
 use crate::vector::{Vector2F, vec2f};
 use pathfinder_simd::default::F32x4;
-use std::ops::{Add, Mul, MulAssign, Sub};
+use core::fmt::{self, Debug, Display, Formatter};
+use core::ops::{Add, Mul, MulAssign, Sub};
 
-#[derive(Clone, Copy, Debug, PartialEq, Default)]
+#[derive(Clone, Copy, PartialEq, Default)]
 pub struct LineSegment2F(pub F32x4);
 
 impl LineSegment2F {
@@ -207,7 +209,7 @@ impl LineSegment2F {
 
     #[inline]
     pub fn length(self) -> f32 {
-        self.square_length().sqrt()
+        math::sqrt(self.square_length())
     }
 
     #[inline]
@@ -252,6 +254,20 @@ impl LineSegment2F {
     }
 }
 
+impl Debug for LineSegment2F {
+    #[inline]
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "{:?}->{:?}", self.from(), self.to())
+    }
+}
+
+impl Display for LineSegment2F {
+    #[inline]
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "{}->{}", self.from(), self.to())
+    }
+}
+
 impl Add<Vector2F> for LineSegment2F {
     type Output = LineSegment2F;
     #[inline]
@@ -299,3 +315,20 @@ pub struct LineSegmentU16 {
     pub to_x: u16,
     pub to_y: u16,
 }
+
+#[cfg(test)]
+mod test {
+    use crate::line_segment::LineSegment2F;
+    use crate::vector::vec2f;
+
+    #[test]
+    fn test_readable_formatting() {
+        let point = vec2f(1.5, -2.0);
+        assert_eq!(format!("{:?}", point), "(1.5, -2.0)");
+        assert_eq!(format!("{}", point), "(1.5, -2)");
+
+        let segment = LineSegment2F::new(vec2f(0.0, 1.0), vec2f(16.0, 0.25));
+        assert_eq!(format!("{:?}", segment), "(0.0, 1.0)->(16.0, 0.25)");
+        assert_eq!(format!("{}", segment), "(0, 1)->(16, 0.25)");
+    }
+}
@@ -0,0 +1,59 @@
+// pathfinder/geometry/src/math.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Floating-point functions that `core` lacks, taken from `libm` when building without `std`.
+
+#[inline]
+pub(crate) fn sqrt(x: f32) -> f32 {
+    #[cfg(feature = "std")]
+    return x.sqrt();
+    #[cfg(not(feature = "std"))]
+    return libm::sqrtf(x);
+}
+
+#[inline]
+pub(crate) fn sin(x: f32) -> f32 {
+    #[cfg(feature = "std")]
+    return x.sin();
+    #[cfg(not(feature = "std"))]
+    return libm::sinf(x);
+}
+
+#[inline]
+pub(crate) fn cos(x: f32) -> f32 {
+    #[cfg(feature = "std")]
+    return x.cos();
+    #[cfg(not(feature = "std"))]
+    return libm::cosf(x);
+}
+
+#[inline]
+pub(crate) fn tan(x: f32) -> f32 {
+    #[cfg(feature = "std")]
+    return x.tan();
+    #[cfg(not(feature = "std"))]
+    return libm::tanf(x);
+}
+
+#[inline]
+pub(crate) fn acos(x: f32) -> f32 {
+    #[cfg(feature = "std")]
+    return x.acos();
+    #[cfg(not(feature = "std"))]
+    return libm::acosf(x);
+}
+
+#[inline]
+pub(crate) fn atan2(y: f32, x: f32) -> f32 {
+    #[cfg(feature = "std")]
+    return y.atan2(x);
+    #[cfg(not(feature = "std"))]
+    return libm::atan2f(y, x);
+}
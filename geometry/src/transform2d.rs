@@ -11,12 +11,13 @@
 //! 2D affine transforms.
 
 use crate::line_segment::LineSegment2F;
+use crate::math;
 use crate::rect::RectF;
 use crate::transform3d::Transform4F;
 use crate::unit_vector::UnitVector;
 use crate::vector::{IntoVector2F, Vector2F, vec2f};
 use pathfinder_simd::default::F32x4;
-use std::ops::{Mul, MulAssign, Sub};
+use core::ops::{Mul, MulAssign, Sub};
 
 /// A 2x2 matrix, optimized with SIMD, in column-major order.
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -102,6 +103,13 @@ impl Matrix2x2F {
     pub fn m22(&self) -> f32 {
         self.0[3]
     }
+
+    /// Returns true if every component of this matrix is within `epsilon` of the corresponding
+    /// component of `other`.
+    #[inline]
+    pub fn approx_eq(&self, other: &Matrix2x2F, epsilon: f32) -> bool {
+        self.0.approx_eq(other.0, epsilon)
+    }
 }
 
 impl Sub<Matrix2x2F> for Matrix2x2F {
@@ -174,6 +182,11 @@ impl Transform2F {
         Transform2F { matrix: Matrix2x2F::default(), vector }
     }
 
+    /// Creates the transform `scale * rotation * translation`, which translates, then rotates,
+    /// then scales.
+    ///
+    /// Use `from_translation_rotation_scale()` for a transform that `translation()`,
+    /// `rotation()`, and `scale_factor()` decompose back into its parts.
     #[inline]
     pub fn from_scale_rotation_translation<S>(scale: S, theta: f32, translation: Vector2F)
                                               -> Transform2F where S: IntoVector2F {
@@ -183,6 +196,18 @@ impl Transform2F {
         Transform2F::from_scale(scale) * rotation * translation
     }
 
+    /// Creates the transform `translation * rotation * scale`, which scales, then rotates, then
+    /// translates. This is the order that `translation()`, `rotation()`, and `scale_factor()`
+    /// assume.
+    #[inline]
+    pub fn from_translation_rotation_scale<S>(translation: Vector2F, theta: f32, scale: S)
+                                              -> Transform2F where S: IntoVector2F {
+        let scale = scale.into_vector_2f();
+        let rotation = Transform2F::from_rotation(theta);
+        let translation = Transform2F::from_translation(translation);
+        translation * rotation * Transform2F::from_scale(scale)
+    }
+
     #[inline]
     pub fn row_major(m11: f32, m12: f32, m13: f32, m21: f32, m22: f32, m23: f32) -> Transform2F {
         Transform2F {
@@ -219,6 +244,14 @@ impl Transform2F {
         *self == Transform2F::default()
     }
 
+    /// Returns true if every component of the matrix and translation of this transform is within
+    /// `epsilon` of the corresponding component of `other`.
+    #[inline]
+    pub fn approx_eq(&self, other: &Transform2F, epsilon: f32) -> bool {
+        self.matrix.approx_eq(&other.matrix, epsilon) &&
+            self.vector.0.approx_eq(other.vector.0, epsilon)
+    }
+
     /// Extracts the scale from this matrix.
     #[inline]
     pub fn extract_scale(&self) -> Vector2F {
@@ -279,7 +312,7 @@ impl Transform2F {
     /// This decomposition assumes that scale, rotation, and translation are applied in that order.
     #[inline]
     pub fn rotation(&self) -> f32 {
-        f32::atan2(self.m21(), self.m11())
+        math::atan2(self.m21(), self.m11())
     }
 
     /// Returns the scale factor of this matrix.
@@ -296,6 +329,22 @@ impl Transform2F {
         let vector_inv = -(matrix_inv * self.vector);
         Transform2F { matrix: matrix_inv, vector: vector_inv }
     }
+
+    /// Returns the inverse of this transform, or `None` if it isn't invertible.
+    ///
+    /// A transform counts as singular when its determinant is zero to within floating point
+    /// precision relative to the size of its entries, e.g. when it scales everything down to a
+    /// line or a point.
+    #[inline]
+    pub fn checked_inverse(&self) -> Option<Transform2F> {
+        let det = self.matrix.det();
+        let entries = self.matrix.0.abs();
+        let max_entry = entries[0].max(entries[1]).max(entries[2]).max(entries[3]);
+        if !det.is_finite() || det.abs() <= f32::EPSILON * max_entry * max_entry {
+            return None;
+        }
+        Some(self.inverse())
+    }
 }
 
 impl Mul<Transform2F> for Transform2F {
@@ -321,7 +370,11 @@ impl Mul<LineSegment2F> for Transform2F {
     type Output = LineSegment2F;
     #[inline]
     fn mul(self, line_segment: LineSegment2F) -> LineSegment2F {
-        LineSegment2F::new(self * line_segment.from(), self * line_segment.to())
+        // Transform both endpoints at once.
+        let (matrix, vector) = (self.matrix.0, self.vector.0);
+        LineSegment2F(matrix.xyxy() * line_segment.0.xxzz() +
+                      matrix.zwzw() * line_segment.0.yyww() +
+                      vector.concat_xy_xy(vector))
     }
 }
 
@@ -343,3 +396,95 @@ impl MulAssign for Transform2F {
         *self = *self * other
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::transform2d::Transform2F;
+    use crate::vector::vec2f;
+    use core::f32::consts::FRAC_PI_2;
+    use quickcheck::{self, TestResult};
+
+    #[test]
+    fn test_approx_eq() {
+        let a = Transform2F::row_major(3.0, 1.0, 4.0, 1.0, 5.0, 9.0);
+        let b = Transform2F::row_major(3.0, 1.0, 4.0, 1.0, 5.0, 9.0);
+        assert_eq!(a, b);
+        assert!(a.approx_eq(&b, 0.0));
+
+        let c = Transform2F::row_major(3.0, 1.0 + 1e-5, 4.0, 1.0, 5.0, 9.0 - 1e-5);
+        assert_ne!(a, c);
+        assert!(a.approx_eq(&c, 1e-4));
+        assert!(!a.approx_eq(&c, 1e-6));
+    }
+
+    #[test]
+    fn test_rotation_and_translation() {
+        let rotated = Transform2F::from_rotation(FRAC_PI_2) * vec2f(1.0, 0.0);
+        assert!((rotated - vec2f(0.0, 1.0)).length() < 1e-6, "{:?}", rotated);
+
+        let (a, b) = (vec2f(3.0, -4.0), vec2f(0.5, 7.0));
+        let translation = Transform2F::from_translation(a) * Transform2F::from_translation(b);
+        assert_eq!(translation, Transform2F::from_translation(a + b));
+        assert_eq!(Transform2F::from_translation(a).translate(b), translation);
+        assert_eq!(translation * vec2f(1.0, 1.0), vec2f(4.5, 4.0));
+    }
+
+    #[test]
+    fn test_scale_rotation_translation_translates_first() {
+        let transform = Transform2F::from_scale_rotation_translation(2.0,
+                                                                     FRAC_PI_2,
+                                                                     vec2f(10.0, 20.0));
+        // (1, 0) moves to (11, 20), rotates to (-20, 11), and scales to (-40, 22).
+        let point = transform * vec2f(1.0, 0.0);
+        assert!((point - vec2f(-40.0, 22.0)).length() < 1e-4, "{:?}", point);
+    }
+
+    #[test]
+    fn test_translation_rotation_scale_decomposes() {
+        let transform = Transform2F::from_translation_rotation_scale(vec2f(10.0, 20.0),
+                                                                     FRAC_PI_2,
+                                                                     2.0);
+        // (1, 0) scales to (2, 0), rotates to (0, 2), and moves to (10, 22).
+        let point = transform * vec2f(1.0, 0.0);
+        assert!((point - vec2f(10.0, 22.0)).length() < 1e-5, "{:?}", point);
+        assert_eq!(transform.translation(), vec2f(10.0, 20.0));
+        assert!((transform.rotation() - FRAC_PI_2).abs() < 1e-6);
+        assert!((transform.scale_factor() - 2.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_checked_inverse_rejects_singular_transforms() {
+        assert!(Transform2F::from_scale(0.0).checked_inverse().is_none());
+        assert!(Transform2F::row_major(1.0, 2.0, 5.0, 2.0, 4.0, 6.0).checked_inverse().is_none());
+        let inverse = Transform2F::from_scale(1e-6).checked_inverse().unwrap();
+        assert!((inverse * vec2f(1e-6, 0.0) - vec2f(1.0, 0.0)).length() < 1e-5);
+    }
+
+    #[test]
+    fn test_checked_inverse_round_trips() {
+        quickcheck::quickcheck(prop_inverse_round_trips as
+                               fn((f32, f32, f32, f32), (f32, f32), (f32, f32)) -> TestResult);
+
+        fn prop_inverse_round_trips(matrix: (f32, f32, f32, f32),
+                                    (m13, m23): (f32, f32),
+                                    (x, y): (f32, f32))
+                                    -> TestResult {
+            // Keep the transform well-conditioned so that the round trip stays precise.
+            let (m11, m12, m21, m22) = (matrix.0 % 10.0, matrix.1 % 10.0,
+                                        matrix.2 % 10.0, matrix.3 % 10.0);
+            if (m11 * m22 - m12 * m21).abs() < 1.0 {
+                return TestResult::discard();
+            }
+
+            let transform = Transform2F::row_major(m11, m12, m13, m21, m22, m23);
+            let inverse = match transform.checked_inverse() {
+                None => return TestResult::failed(),
+                Some(inverse) => inverse,
+            };
+            let point = vec2f(x, y);
+            let epsilon = 1e-3 * (1.0 + point.length());
+            TestResult::from_bool((inverse * transform * point - point).length() < epsilon &&
+                                  (transform * inverse * point - point).length() < epsilon)
+        }
+    }
+}
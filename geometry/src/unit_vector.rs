@@ -10,6 +10,7 @@
 
 //! A utility module that allows unit vectors to be treated like angles.
 
+use crate::math;
 use crate::vector::Vector2F;
 use pathfinder_simd::default::F32x2;
 
@@ -19,7 +20,7 @@ pub struct UnitVector(pub Vector2F);
 impl UnitVector {
     #[inline]
     pub fn from_angle(theta: f32) -> UnitVector {
-        UnitVector(Vector2F::new(theta.cos(), theta.sin()))
+        UnitVector(Vector2F::new(math::cos(theta), math::sin(theta)))
     }
 
     /// Angle addition formula.
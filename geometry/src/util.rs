@@ -10,7 +10,7 @@
 
 //! Various utilities.
 
-use std::f32;
+use core::f32;
 
 pub const EPSILON: f32 = 0.001;
 
@@ -10,12 +10,14 @@
 
 //! A SIMD-optimized point type.
 
+use crate::math;
 use pathfinder_simd::default::{F32x2, F32x4, I32x2};
-use std::hash::{Hash, Hasher};
-use std::ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Sub, SubAssign};
+use core::fmt::{self, Debug, Display, Formatter};
+use core::hash::{Hash, Hasher};
+use core::ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Sub, SubAssign};
 
 /// 2D points with 32-bit floating point coordinates.
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Default)]
 pub struct Vector2F(pub F32x2);
 
 impl Vector2F {
@@ -116,7 +118,7 @@ impl Vector2F {
     /// Treats this point as a vector and calculates its length.
     #[inline]
     pub fn length(self) -> f32 {
-        f32::sqrt(self.square_length())
+        math::sqrt(self.square_length())
     }
 
     /// Treats this point as a vector and normalizes it.
@@ -146,7 +148,7 @@ impl Vector2F {
     /// Returns the counterclockwise angle of the vector from the +x axis.
     #[inline]
     pub fn angle(self) -> f32 {
-        self.y().atan2(self.x())
+        math::atan2(self.y(), self.x())
     }
 
     /// Returns the coefficient when the given vector `a` is projected onto this one.
@@ -161,7 +163,7 @@ impl Vector2F {
     /// Returns the angle between the two vectors.
     #[inline]
     pub fn angle_between(self, a: Vector2F) -> f32 {
-        self.projection_coefficient(a).acos()
+        math::acos(self.projection_coefficient(a))
     }
 
     #[inline]
@@ -198,6 +200,20 @@ impl PartialEq for Vector2F {
     }
 }
 
+impl Debug for Vector2F {
+    #[inline]
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "({:?}, {:?})", self.x(), self.y())
+    }
+}
+
+impl Display for Vector2F {
+    #[inline]
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "({}, {})", self.x(), self.y())
+    }
+}
+
 impl Add<Vector2F> for Vector2F {
     type Output = Vector2F;
     #[inline]
@@ -515,7 +531,7 @@ impl Vector3F {
 
     #[inline]
     pub fn length(self) -> f32 {
-        f32::sqrt(self.square_length())
+        math::sqrt(self.square_length())
     }
 
     #[inline]
@@ -18,10 +18,12 @@ use crate::gpu_data::{DrawTileBatch, DrawTileBatchD3D9, DrawTileBatchD3D11, Fill
 use crate::gpu_data::{PathBatchIndex, PathSource, PrepareTilesInfoD3D11, PropagateMetadataD3D11};
 use crate::gpu_data::{RenderCommand, SegmentIndicesD3D11, SegmentsD3D11, TileBatchDataD3D11};
 use crate::gpu_data::{TileBatchId, TileBatchTexture, TileObjectPrimitive, TilePathInfoD3D11};
-use crate::options::{PrepareMode, PreparedBuildOptions, PreparedRenderTransform};
+#[cfg(feature="d3d9")]
+use crate::gpu_data::TileObjectLists;
+use crate::options::{BuildError, PrepareMode, PreparedBuildOptions, PreparedRenderTransform};
 use crate::paint::{PaintId, PaintInfo, PaintMetadata};
 use crate::scene::{ClipPathId, DisplayItem, DrawPath, DrawPathId, LastSceneInfo, PathId};
-use crate::scene::{Scene, SceneSink};
+use crate::scene::{PathTileCounts, Scene, SceneSink};
 use crate::tile_map::DenseTileMap;
 use crate::tiler::Tiler;
 use crate::tiles::{self, DrawTilingPathInfo, TILE_HEIGHT, TILE_WIDTH, TilingPathInfo};
@@ -33,12 +35,12 @@ use pathfinder_content::outline::{Outline, PointFlags};
 use pathfinder_geometry::line_segment::{LineSegment2F, LineSegmentU16};
 use pathfinder_geometry::rect::{RectF, RectI};
 use pathfinder_geometry::transform2d::Transform2F;
-use pathfinder_geometry::vector::{Vector2I, vec2i};
+use pathfinder_geometry::vector::{Vector2I, vec2f, vec2i};
 use pathfinder_gpu::TextureSamplingFlags;
 use pathfinder_simd::default::F32x4;
 use std::borrow::Cow;
 use std::ops::Range;
-use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::u32;
 
 pub(crate) const ALPHA_TILE_LEVEL_COUNT: usize = 2;
@@ -51,9 +53,14 @@ const MAX_CLIP_BATCHES: u32 = 32;
 
 pub(crate) struct SceneBuilder<'a, 'b, 'c, 'd> {
     pub(crate) scene: &'a mut Scene,
-    built_options: &'b PreparedBuildOptions,
+    pub(crate) built_options: &'b PreparedBuildOptions,
     next_alpha_tile_indices: [AtomicUsize; ALPHA_TILE_LEVEL_COUNT],
+    // The number of fills sent so far, checked against `max_total_fills`.
+    fill_count: AtomicUsize,
+    fill_limit_exceeded: AtomicBool,
     pub(crate) sink: &'c mut SceneSink<'d>,
+    // The tile counts of each draw path, if tiling was done on CPU.
+    pub(crate) draw_path_tile_counts: Vec<PathTileCounts>,
 }
 
 #[derive(Debug)]
@@ -61,6 +68,10 @@ pub(crate) struct ObjectBuilder {
     pub built_path: BuiltPath,
     pub fills: Vec<Fill>,
     pub bounds: RectF,
+    /// During CPU tiling, the change in winding number at the top of each tile. These are kept
+    /// exact, since saturated deltas would not cancel out, and are only narrowed to the tiles'
+    /// backdrops once propagated.
+    pub backdrop_deltas: Vec<i32>,
 }
 
 // Derives `Clone` just so we can use `Cow`, not because we actually want to clone it.
@@ -74,6 +85,7 @@ struct BuiltDrawPath {
     sampling_flags_1: TextureSamplingFlags,
     mask_0_fill_rule: FillRule,
     occludes: bool,
+    layer: u8,
 }
 
 impl BuiltDrawPath {
@@ -90,6 +102,7 @@ impl BuiltDrawPath {
             mask_0_fill_rule: path_object.fill_rule(),
             blend_mode,
             occludes,
+            layer: path_object.layer(),
         }
     }
 }
@@ -141,11 +154,14 @@ impl<'a, 'b, 'c, 'd> SceneBuilder<'a, 'b, 'c, 'd> {
             scene,
             built_options,
             next_alpha_tile_indices: [AtomicUsize::new(0), AtomicUsize::new(0)],
+            fill_count: AtomicUsize::new(0),
+            fill_limit_exceeded: AtomicBool::new(false),
             sink,
+            draw_path_tile_counts: vec![],
         }
     }
 
-    pub fn build<E>(&mut self, executor: &E) where E: Executor {
+    pub fn build<E>(&mut self, executor: &E) -> Result<(), BuildError> where E: Executor {
         let start_time = Instant::now();
 
         // Send the start rendering command.
@@ -163,6 +179,14 @@ impl<'a, 'b, 'c, 'd> SceneBuilder<'a, 'b, 'c, 'd> {
             needs_readable_framebuffer,
         });
 
+        // With nothing to draw, skip straight to the end rather than building paints and
+        // allocating an empty Z-buffer and tile batches.
+        if draw_path_count == 0 {
+            let cpu_build_time = Instant::now() - start_time;
+            self.sink.listener.send(RenderCommand::Finish { cpu_build_time });
+            return Ok(());
+        }
+
         let prepare_mode = self.built_options.to_prepare_mode(self.sink.renderer_level);
 
         let render_transform = match self.built_options.transform {
@@ -186,6 +210,15 @@ impl<'a, 'b, 'c, 'd> SceneBuilder<'a, 'b, 'c, 'd> {
             PrepareMode::GPU { .. } => None,
         };
 
+        // Drop the partially built paths. No tiles are sent, so the fills already sent are never
+        // drawn.
+        if self.fill_limit_exceeded.load(Ordering::Relaxed) {
+            let cpu_build_time = Instant::now() - start_time;
+            self.sink.listener.send(RenderCommand::Finish { cpu_build_time });
+            let max_total_fills = self.built_options.max_total_fills.unwrap();
+            return Err(BuildError::TooManyFills { max_total_fills });
+        }
+
 
         #[cfg(feature="d3d11")]
         {
@@ -219,6 +252,7 @@ impl<'a, 'b, 'c, 'd> SceneBuilder<'a, 'b, 'c, 'd> {
 
         let cpu_build_time = Instant::now() - start_time;
         self.sink.listener.send(RenderCommand::Finish { cpu_build_time });
+        Ok(())
     }
 
     fn build_paths_on_cpu<E>(&mut self,
@@ -241,6 +275,8 @@ impl<'a, 'b, 'c, 'd> SceneBuilder<'a, 'b, 'c, 'd> {
             })
         });
 
+        let occluded_draw_paths = self.find_occluded_draw_paths(paint_metadata);
+
         let built_draw_paths = executor.build_vector(draw_path_count, |path_index| {
             self.build_draw_path_on_cpu(DrawPathBuildParams {
                 path_build_params: PathBuildParams {
@@ -252,12 +288,76 @@ impl<'a, 'b, 'c, 'd> SceneBuilder<'a, 'b, 'c, 'd> {
                 },
                 paint_metadata: &paint_metadata,
                 built_clip_paths: &built_clip_paths,
+                occluded: occluded_draw_paths[path_index],
             })
         });
 
+        self.draw_path_tile_counts = built_draw_paths.iter().map(|built_draw_path| {
+            built_draw_path.path.tile_counts()
+        }).collect();
+
         BuiltPaths { draw: built_draw_paths }
     }
 
+    /// Returns, for each draw path, whether a later opaque rectangle drawn to the same render
+    /// target and layer covers every pixel the path could touch, in which case tiling it can be
+    /// skipped.
+    ///
+    /// This is a conservative broad phase on bounding boxes: only unclipped, axis-aligned
+    /// rectangles count as occluders, and nothing is culled under dilation or under transforms
+    /// that don't keep rectangles axis-aligned.
+    fn find_occluded_draw_paths(&self, paint_metadata: &[PaintMetadata]) -> Vec<bool> {
+        let mut occluded = vec![false; self.scene.draw_paths().len()];
+        if !self.built_options.dilation.is_zero() {
+            return occluded;
+        }
+
+        let mut transform = match self.built_options.transform {
+            PreparedRenderTransform::None => Transform2F::default(),
+            PreparedRenderTransform::Transform2D(transform) if transform.m12() == 0.0 &&
+                    transform.m21() == 0.0 => transform,
+            _ => return occluded,
+        };
+        if self.built_options.subpixel_aa_enabled {
+            transform *= Transform2F::from_scale(vec2f(3.0, 1.0))
+        }
+
+        for display_item in self.scene.display_list() {
+            let draw_path_id_range = match *display_item {
+                DisplayItem::DrawPaths(ref draw_path_id_range) => draw_path_id_range,
+                DisplayItem::PushRenderTarget(_) | DisplayItem::PopRenderTarget => continue,
+            };
+
+            // Walk front to back, collecting the rectangles that opaque paths cover completely.
+            let mut occluder_rects: Vec<(u8, RectF)> = vec![];
+            for draw_path_index in (draw_path_id_range.start.0..draw_path_id_range.end.0).rev() {
+                let draw_path = self.scene.get_draw_path(DrawPathId(draw_path_index));
+                let layer = draw_path.layer();
+
+                // Pixels on the edge of an occluder are only partially covered, so compare
+                // against the whole pixels that this path might touch.
+                let bounds = (transform * draw_path.outline().bounds()).round_out();
+                if occluder_rects.iter().any(|&(occluder_layer, rect)| {
+                    occluder_layer == layer && rect.contains_rect(bounds)
+                }) {
+                    occluded[draw_path_index as usize] = true;
+                    continue;
+                }
+
+                let paint_metadata = &paint_metadata[draw_path.paint().0 as usize];
+                if draw_path.clip_path().is_some() || !paint_metadata.is_opaque ||
+                        !draw_path.blend_mode().occludes_backdrop() {
+                    continue;
+                }
+                if let Some(rect) = draw_path.outline().as_axis_aligned_rect() {
+                    occluder_rects.push((layer, transform * rect));
+                }
+            }
+        }
+
+        occluded
+    }
+
     fn build_clip_path_on_cpu(&self, params: PathBuildParams) -> BuiltPath {
         let PathBuildParams { path_id, view_box, built_options, scene, prepare_mode } = params;
         let path_object = &scene.get_clip_path(path_id.to_clip_path_id());
@@ -289,10 +389,15 @@ impl<'a, 'b, 'c, 'd> SceneBuilder<'a, 'b, 'c, 'd> {
             },
             paint_metadata,
             built_clip_paths,
+            occluded,
         } = params;
 
         let path_object = scene.get_draw_path(path_id.to_draw_path_id());
-        let outline = scene.apply_render_options(path_object.outline(), built_options);
+        let outline = if occluded || self.fill_limit_exceeded.load(Ordering::Relaxed) {
+            Outline::new()
+        } else {
+            scene.apply_render_options(path_object.outline(), built_options)
+        };
 
         let paint_id = path_object.paint();
         let paint_metadata = &paint_metadata[paint_id.0 as usize];
@@ -314,10 +419,24 @@ impl<'a, 'b, 'c, 'd> SceneBuilder<'a, 'b, 'c, 'd> {
         tiler.generate_tiles();
         self.send_fills(tiler.object_builder.fills);
 
+        debug!("built draw path {:?}: {:?}",
+               path_id,
+               tiler.object_builder.built_path.tile_counts());
+
         BuiltDrawPath::new(tiler.object_builder.built_path, path_object, paint_metadata)
     }
 
     fn send_fills(&self, fills: Vec<Fill>) {
+        if let Some(max_total_fills) = self.built_options.max_total_fills {
+            let fill_count = self.fill_count.fetch_add(fills.len(), Ordering::Relaxed);
+            if fill_count + fills.len() > max_total_fills {
+                self.fill_limit_exceeded.store(true, Ordering::Relaxed);
+            }
+            if self.fill_limit_exceeded.load(Ordering::Relaxed) {
+                return;
+            }
+        }
+
         #[cfg(feature="d3d9")]
         if !fills.is_empty() {
             self.sink.listener.send(RenderCommand::AddFillsD3D9(fills));
@@ -409,6 +528,7 @@ struct DrawPathBuildParams<'a> {
     path_build_params: PathBuildParams<'a>,
     paint_metadata: &'a [PaintMetadata],
     built_clip_paths: &'a [BuiltPath],
+    occluded: bool,
 }
 
 impl BuiltPath {
@@ -482,6 +602,26 @@ impl BuiltPath {
             paint_id,
         }
     }
+
+    /// Counts the tiles that this path's tile rect covers, split into solid and mask tiles.
+    ///
+    /// Solid and mask tiles are only known if tiling was done on CPU. Otherwise, they're zero.
+    pub(crate) fn tile_counts(&self) -> PathTileCounts {
+        let (solid_tiles, mask_tiles) = match self.data {
+            BuiltPathData::CPU(ref data) => {
+                let solid_tiles = data.tiles.data.iter().filter(|tile| {
+                    tile.alpha_tile_id == AlphaTileId(!0) &&
+                        tiles::backdrop_fills_tile(tile.backdrop, self.fill_rule)
+                }).count();
+                let mask_tiles = data.tiles.data.iter().filter(|tile| {
+                    tile.alpha_tile_id != AlphaTileId(!0)
+                }).count();
+                (solid_tiles, mask_tiles)
+            }
+            BuiltPathData::TransformCPUBinGPU(_) | BuiltPathData::GPU => (0, 0),
+        };
+        PathTileCounts { tiles: self.tile_bounds.area() as usize, solid_tiles, mask_tiles }
+    }
 }
 
 // Utilities for built objects
@@ -503,7 +643,11 @@ impl ObjectBuilder {
                                         prepare_mode,
                                         clip_path_id,
                                         tiling_path_info);
-        ObjectBuilder { built_path, bounds: path_bounds, fills: vec![] }
+        let backdrop_deltas = match built_path.data {
+            BuiltPathData::CPU(ref tiled_data) => vec![0; tiled_data.tiles.data.len()],
+            BuiltPathData::TransformCPUBinGPU(_) | BuiltPathData::GPU => vec![],
+        };
+        ObjectBuilder { built_path, bounds: path_bounds, fills: vec![], backdrop_deltas }
     }
 
     pub(crate) fn add_fill(&mut self,
@@ -513,9 +657,10 @@ impl ObjectBuilder {
         debug!("add_fill({:?} ({:?}))", segment, tile_coords);
 
         // Ensure this fill is in bounds. If not, cull it.
-        if self.tile_coords_to_local_index(tile_coords).is_none() {
-            return;
-        }
+        let local_tile_index = match self.tile_coords_to_local_index(tile_coords) {
+            None => return,
+            Some(local_tile_index) => local_tile_index,
+        };
 
         debug_assert_eq!(TILE_WIDTH, TILE_HEIGHT);
 
@@ -535,8 +680,28 @@ impl ObjectBuilder {
             return;
         }
 
+        // A single path can exceed the limit on its own, so stop accumulating its fills here
+        // rather than waiting for them to be sent.
+        if let Some(max_total_fills) = scene_builder.built_options.max_total_fills {
+            if self.fills.len() >= max_total_fills {
+                scene_builder.fill_limit_exceeded.store(true, Ordering::Relaxed);
+                return;
+            }
+        }
+
+        // A fill running along the whole top of the tile covers all of it, which backdrops can
+        // express without a mask: add its winding from this row down, then take it back out from
+        // the next row down. Edges snapped to the tile grid then only ever produce solid tiles.
+        let max = TILE_WIDTH as i32 * 256 - 1;
+        if from_y == 0 && to_y == 0 && (from_x.min(to_x), from_x.max(to_x)) == (0, max) {
+            let delta = if to_x > from_x { -1 } else { 1 };
+            self.adjust_alpha_tile_backdrop(tile_coords - vec2i(0, 1), delta);
+            self.adjust_alpha_tile_backdrop(tile_coords, -delta);
+            return;
+        }
+
         // Allocate a global tile if necessary.
-        let alpha_tile_id = self.get_or_allocate_alpha_tile_index(scene_builder, tile_coords);
+        let alpha_tile_id = self.get_or_allocate_alpha_tile_index(scene_builder, local_tile_index);
 
         // Pack instance data.
         debug!("... OK, pushing");
@@ -554,10 +719,9 @@ impl ObjectBuilder {
 
     fn get_or_allocate_alpha_tile_index(&mut self,
                                         scene_builder: &SceneBuilder,
-                                        tile_coords: Vector2I)
+                                        local_tile_index: u32)
                                         -> AlphaTileId {
-        let local_tile_index = self.tile_coords_to_local_index_unchecked(tile_coords) as usize;
-
+        let local_tile_index = local_tile_index as usize;
         let tiles = match self.built_path.data {
             BuiltPathData::CPU(ref mut cpu_data) => &mut cpu_data.tiles,
             BuiltPathData::GPU | BuiltPathData::TransformCPUBinGPU(_) => {
@@ -612,7 +776,7 @@ impl ObjectBuilder {
         }
 
         let local_tile_index = tiles.coords_to_index_unchecked(tile_coords);
-        tiles.data[local_tile_index].backdrop += delta;
+        self.backdrop_deltas[local_tile_index] += delta as i32;
     }
 }
 
@@ -890,10 +1054,16 @@ impl TileBatchBuilder {
                                                      draw_path_id_range: Range<DrawPathId>,
                                                      paint_metadata: &[PaintMetadata],
                                                      prepare_mode: &PrepareMode) {
+        // Composite layers in ascending order, and the paths within each layer in the order they
+        // were pushed.
+        let mut draw_path_ids: Vec<_> =
+            (draw_path_id_range.start.0..draw_path_id_range.end.0).map(DrawPathId).collect();
+        draw_path_ids.sort_by_key(|&draw_path_id| scene.get_draw_path(draw_path_id).layer());
+
         let mut draw_tile_batch = None;
-        for draw_path_id in draw_path_id_range.start.0..draw_path_id_range.end.0 {
-            let draw_path_id = DrawPathId(draw_path_id);
-            let draw_path = match self.level {
+        let mut batch_layer = 0;
+        for draw_path_id in draw_path_ids {
+            let draw_path: Cow<BuiltDrawPath> = match self.level {
                 #[cfg(feature="d3d11")]
                 TileBatchBuilderLevel::D3D11 { .. } => {
                     match self.prepare_draw_path_for_gpu_binning(scene,
@@ -911,8 +1081,9 @@ impl TileBatchBuilder {
                 }
             };
 
-            // Try to reuse the current batch if we can.
-            let flush_needed = match draw_tile_batch {
+            // Try to reuse the current batch if we can. Each batch has its own Z-buffer, so
+            // switching layers always starts a new batch.
+            let flush_needed = batch_layer != draw_path.layer || match draw_tile_batch {
                 #[cfg(feature="d3d11")]
                 Some(DrawTileBatch::D3D11(ref mut existing_batch)) => {
                     !fixup_batch_for_new_path_if_possible(&mut existing_batch.color_texture,
@@ -951,6 +1122,11 @@ impl TileBatchBuilder {
                             tiles: vec![],
                             clips: vec![],
                             z_buffer_data: DenseTileMap::from_builder(|_| 0, tile_bounds),
+                            tile_objects: if built_options.record_tile_objects {
+                                Some(TileObjectLists::new(tile_bounds))
+                            } else {
+                                None
+                            },
                             color_texture: draw_path.color_texture,
                             filter: draw_path.filter,
                             blend_mode: draw_path.blend_mode,
@@ -967,6 +1143,7 @@ impl TileBatchBuilder {
                     }
                 };
                 self.next_batch_id.0 += 1;
+                batch_layer = draw_path.layer;
             }
 
             // Add clip path if necessary.
@@ -1017,11 +1194,15 @@ impl TileBatchBuilder {
 
                         draw_tile_batch.tiles.push(*tile);
 
+                        let tile_coords = vec2i(tile.tile_x as i32, tile.tile_y as i32);
+                        if let Some(ref mut tile_objects) = draw_tile_batch.tile_objects {
+                            tile_objects.push(tile_coords, draw_path_id.to_path_id());
+                        }
+
                         if !draw_path.occludes || tile.alpha_tile_id != AlphaTileId(!0) {
                             continue;
                         }
 
-                        let tile_coords = vec2i(tile.tile_x as i32, tile.tile_y as i32);
                         let z_value = draw_tile_batch.z_buffer_data
                                                      .get_mut(tile_coords)
                                                      .expect("Z value out of bounds!");
@@ -1241,3 +1422,502 @@ fn fixup_batch_for_new_path_if_possible(batch_color_texture: &mut Option<TileBat
     }
     true
 }
+
+#[cfg(test)]
+mod test {
+    use super::{BuiltPathData, ObjectBuilder, SceneBuilder};
+    use crate::concurrent::executor::{Executor, SequentialExecutor};
+    use crate::concurrent::rayon::RayonExecutor;
+    use crate::gpu::options::RendererLevel;
+    use crate::gpu_data::{Fill, RenderCommand, TileObjectPrimitive};
+    use crate::options::{BuildError, BuildOptions, PrepareMode, RenderCommandListener};
+    use crate::paint::Paint;
+    use crate::scene::{DrawPath, PathId, PathTileCounts, Scene, SceneSink};
+    #[cfg(feature="d3d9")]
+    use crate::scene::test::build_d3d9;
+    use crate::tiler::Tiler;
+    use crate::tiles::{DrawTilingPathInfo, TILE_HEIGHT, TilingPathInfo};
+    use pathfinder_color::ColorU;
+    use pathfinder_content::effects::BlendMode;
+    use pathfinder_content::fill::FillRule;
+    use pathfinder_content::outline::{Contour, Outline};
+    use pathfinder_geometry::line_segment::LineSegment2F;
+    use pathfinder_geometry::rect::RectF;
+    use pathfinder_geometry::transform2d::Transform2F;
+    use pathfinder_geometry::vector::{Vector2I, vec2f, vec2i};
+
+    // Tiles the outline with a fresh scene builder and passes the result to `f`.
+    fn with_tiled_outline<F>(outline: &Outline, view_box: RectF, f: F) where F: FnOnce(&Tiler) {
+        let mut scene = Scene::new();
+        scene.set_view_box(view_box);
+        let paint_id = scene.push_paint(&Paint::from_color(ColorU::black()));
+
+        let options = BuildOptions::default().prepare(scene.bounds());
+        let listener = RenderCommandListener::new(Box::new(|_| {}));
+        let mut sink = SceneSink::new(listener, RendererLevel::D3D9);
+        let scene_builder = SceneBuilder::new(&mut scene, &options, &mut sink);
+        let mut tiler = Tiler::new(&scene_builder,
+                                   PathId(0),
+                                   outline,
+                                   FillRule::Winding,
+                                   view_box,
+                                   &PrepareMode::CPU,
+                                   None,
+                                   &[],
+                                   TilingPathInfo::Draw(DrawTilingPathInfo {
+            paint_id,
+            blend_mode: BlendMode::SrcOver,
+            fill_rule: FillRule::Winding,
+        }));
+        tiler.generate_tiles();
+        f(&tiler);
+    }
+
+    #[test]
+    fn test_tile_counts_of_rect() {
+        // Covers tiles (0, 0) to (4, 3) inclusive, so 5x4 tiles in total, of which 3x2 are in the
+        // interior.
+        let outline = Outline::from_rect(RectF::new(vec2f(8.0, 8.0), vec2f(64.0, 48.0)));
+        let view_box = RectF::new(vec2f(0.0, 0.0), vec2f(128.0, 128.0));
+        with_tiled_outline(&outline, view_box, |tiler| {
+            assert_eq!(tiler.object_builder.built_path.tile_counts(),
+                       PathTileCounts { tiles: 20, solid_tiles: 6, mask_tiles: 14 });
+        });
+    }
+
+    // Returns the net signed area of the fills in the given tile, in square pixels.
+    fn fill_area_of_tile(tiler: &Tiler, tile_coords: Vector2I) -> f32 {
+        let tiles = match tiler.object_builder.built_path.data {
+            BuiltPathData::CPU(ref cpu_data) => &cpu_data.tiles,
+            _ => unreachable!(),
+        };
+        assert!(tiles.rect.contains_point(tile_coords));
+        let alpha_tile_id = tiles.get(tile_coords).unwrap().alpha_tile_id;
+        tiler.object_builder.fills.iter().filter(|fill| fill.link == alpha_tile_id.0).map(|fill| {
+            let segment = fill.line_segment;
+            let (from_y, to_y) = (segment.from_y as f32 / 256.0, segment.to_y as f32 / 256.0);
+            let width = (segment.to_x as f32 - segment.from_x as f32) / 256.0;
+            width * (TILE_HEIGHT as f32 - (from_y + to_y) * 0.5)
+        }).sum()
+    }
+
+    #[test]
+    fn test_edges_on_tile_boundary_cover_one_row() {
+        let view_box = RectF::new(vec2f(0.0, 0.0), vec2f(32.0, 32.0));
+
+        // Keeps the second row of tiles within the path bounds.
+        let mut marker = Contour::from_rect(RectF::new(vec2f(20.0, 20.0), vec2f(8.0, 8.0)));
+        marker.close();
+
+        // The right edge lands exactly on y = 16, and the bottom edge lies on it.
+        let mut outline = Outline::from_rect(RectF::new(vec2f(2.0, 2.0), vec2f(12.0, 14.0)));
+        outline.push_contour(marker.clone());
+        with_tiled_outline(&outline, view_box, |tiler| {
+            assert_eq!(fill_area_of_tile(tiler, vec2i(0, 0)).abs(), 12.0 * 14.0);
+            assert_eq!(fill_area_of_tile(tiler, vec2i(0, 1)), 0.0);
+        });
+
+        // A shallow edge lands exactly on y = 16 and the contour heads back up.
+        let mut contour = Contour::new();
+        contour.push_endpoint(vec2f(2.0, 12.0));
+        contour.push_endpoint(vec2f(14.0, 16.0));
+        contour.push_endpoint(vec2f(14.0, 4.0));
+        contour.close();
+        let mut outline = Outline::new();
+        outline.push_contour(contour);
+        outline.push_contour(marker);
+        with_tiled_outline(&outline, view_box, |tiler| {
+            assert!(fill_area_of_tile(tiler, vec2i(0, 0)) != 0.0);
+            assert_eq!(fill_area_of_tile(tiler, vec2i(0, 1)), 0.0);
+        });
+    }
+
+    #[test]
+    fn test_edges_on_tile_column_boundary_add_no_zero_width_fills() {
+        // The vertical edge lies exactly on x = 16, and the slanted edges meet it there, so
+        // clamping them to each tile leaves spans with no width on the boundary.
+        let view_box = RectF::new(vec2f(0.0, 0.0), vec2f(48.0, 48.0));
+        let mut contour = Contour::new();
+        contour.push_endpoint(vec2f(16.0, 3.0));
+        contour.push_endpoint(vec2f(40.0, 20.0));
+        contour.push_endpoint(vec2f(16.0, 44.0));
+        contour.push_endpoint(vec2f(16.0, 32.0));
+        contour.push_endpoint(vec2f(5.0, 24.0));
+        contour.push_endpoint(vec2f(16.0, 16.0));
+        contour.close();
+        let mut outline = Outline::new();
+        outline.push_contour(contour);
+        with_tiled_outline(&outline, view_box, |tiler| {
+            let fills = &tiler.object_builder.fills;
+            assert!(!fills.is_empty());
+            for fill in fills {
+                assert_ne!(fill.line_segment.from_x, fill.line_segment.to_x, "{:?}", fill);
+            }
+        });
+    }
+
+    #[test]
+    fn test_high_winding_interior_is_solid_without_fills() {
+        let view_box = RectF::new(vec2f(0.0, 0.0), vec2f(64.0, 64.0));
+        let rect = RectF::new(vec2f(4.0, 4.0), vec2f(56.0, 56.0));
+        for &copies in &[1, 5] {
+            let mut outline = Outline::new();
+            for _ in 0..copies {
+                outline.push_contour(Contour::from_rect(rect));
+            }
+            with_tiled_outline(&outline, view_box, |tiler| {
+                let tiles = match tiler.object_builder.built_path.data {
+                    BuiltPathData::CPU(ref cpu_data) => &cpu_data.tiles,
+                    _ => unreachable!(),
+                };
+                for &tile_coords in &[vec2i(1, 1), vec2i(2, 1), vec2i(1, 2), vec2i(2, 2)] {
+                    let tile = tiles.get(tile_coords).unwrap();
+                    assert!(tile.is_solid(), "{} copies: {:?}", copies, tile);
+                    assert_eq!(tile.backdrop.abs(), 1, "{} copies: {:?}", copies, tile);
+                }
+            });
+        }
+    }
+
+    #[test]
+    fn test_fills_past_tile_edges_are_clamped_to_tile() {
+        let view_box = RectF::new(vec2f(0.0, 0.0), vec2f(64.0, 64.0));
+        let mut scene = Scene::new();
+        scene.set_view_box(view_box);
+        let paint_id = scene.push_paint(&Paint::from_color(ColorU::black()));
+        let options = BuildOptions::default().prepare(scene.bounds());
+        let listener = RenderCommandListener::new(Box::new(|_| {}));
+        let mut sink = SceneSink::new(listener, RendererLevel::D3D9);
+        let scene_builder = SceneBuilder::new(&mut scene, &options, &mut sink);
+        let mut object_builder = ObjectBuilder::new(PathId(0),
+                                                    view_box,
+                                                    view_box,
+                                                    FillRule::Winding,
+                                                    &PrepareMode::CPU,
+                                                    None,
+                                                    &TilingPathInfo::Draw(DrawTilingPathInfo {
+            paint_id,
+            blend_mode: BlendMode::SrcOver,
+            fill_rule: FillRule::Winding,
+        }));
+
+        // Tile (1, 1) spans 16 to 32 on both axes. Rounding can leave the ends of a clipped edge
+        // slightly outside of it; those must land on the tile edges instead of wrapping around
+        // to the far side.
+        let segments = [
+            LineSegment2F::new(vec2f(15.99, 20.0), vec2f(24.0, 28.0)),
+            LineSegment2F::new(vec2f(24.0, 15.99), vec2f(32.01, 32.01)),
+        ];
+        for &segment in &segments {
+            object_builder.add_fill(&scene_builder, segment, vec2i(1, 1));
+        }
+
+        let fills: Vec<_> = object_builder.fills.iter().map(|fill| {
+            let segment = fill.line_segment;
+            (segment.from_x, segment.from_y, segment.to_x, segment.to_y)
+        }).collect();
+        assert_eq!(fills, vec![(0, 4 * 256, 8 * 256, 12 * 256),
+                               (8 * 256, 0, 16 * 256 - 1, 16 * 256 - 1)]);
+    }
+
+    #[test]
+    fn test_tall_near_vertical_edge_crosses_tile_boundaries_exactly() {
+        const ROWS: i32 = 1000;
+        let height = (ROWS * TILE_HEIGHT as i32) as f32;
+        let view_box = RectF::new(vec2f(0.0, 0.0), vec2f(16.0, height));
+
+        // A sliver whose left edge leans by half a pixel over a thousand tiles.
+        let mut contour = Contour::new();
+        contour.push_endpoint(vec2f(12.0, 0.0));
+        contour.push_endpoint(vec2f(4.0, 0.0));
+        contour.push_endpoint(vec2f(4.5, height));
+        contour.push_endpoint(vec2f(12.0, height));
+        contour.close();
+        let mut outline = Outline::new();
+        outline.push_contour(contour);
+
+        with_tiled_outline(&outline, view_box, |tiler| {
+            // Each tile the left edge enters from above gets an auxiliary fill from the tile's
+            // upper left corner to the crossing point, which must lie on the top boundary.
+            let aux_fills: Vec<_> = tiler.object_builder.fills.iter().filter(|fill| {
+                fill.line_segment.from_x == 0 && fill.line_segment.from_y == 0
+            }).collect();
+            assert_eq!(aux_fills.len(), ROWS as usize - 1);
+            for fill in aux_fills {
+                assert_eq!(fill.line_segment.to_y, 0, "{:?}", fill);
+            }
+
+            for tile_y in 0..ROWS {
+                let left = 4.0 + 0.5 * (tile_y as f32 + 0.5) / ROWS as f32;
+                let expected_area = (12.0 - left) * TILE_HEIGHT as f32;
+                let area = fill_area_of_tile(tiler, vec2i(0, tile_y)).abs();
+                assert!((area - expected_area).abs() < 0.1, "{}: {}", tile_y, area);
+            }
+        });
+    }
+
+    #[cfg(feature="d3d9")]
+    #[test]
+    fn test_tile_objects_record_overlapping_paths_in_order() {
+        let mut scene = Scene::new();
+        scene.set_view_box(RectF::new(vec2f(0.0, 0.0), vec2f(128.0, 128.0)));
+        let paint_id = scene.push_paint(&Paint::from_color(ColorU::black()));
+        for &origin in &[8.0, 24.0, 40.0] {
+            let rect = RectF::new(vec2f(origin, origin), vec2f(48.0, 48.0));
+            scene.push_draw_path(DrawPath::new(Outline::from_rect(rect), paint_id));
+        }
+
+        let options = BuildOptions { record_tile_objects: true, ..BuildOptions::default() };
+        let output = build_d3d9(&mut scene, options, &SequentialExecutor);
+
+        let batches: Vec<_> = output.batches().collect();
+        assert_eq!(batches.len(), 1);
+        let tile_objects = batches[0].tile_objects.as_ref().unwrap();
+        // Tile (2, 2) spans (32, 32) to (48, 48), which all three rects touch.
+        assert_eq!(tile_objects.get(vec2i(2, 2)).collect::<Vec<_>>(),
+                   vec![PathId(0), PathId(1), PathId(2)]);
+        assert_eq!(tile_objects.get(vec2i(0, 0)).collect::<Vec<_>>(), vec![PathId(0)]);
+        assert_eq!(tile_objects.get(vec2i(7, 7)).count(), 0);
+    }
+
+    #[cfg(feature="d3d9")]
+    #[test]
+    fn test_empty_scene_sends_only_start_and_finish() {
+        let mut scene = Scene::new();
+        scene.set_view_box(RectF::new(vec2f(0.0, 0.0), vec2f(64.0, 64.0)));
+        scene.push_paint(&Paint::from_color(ColorU::black()));
+
+        let output = build_d3d9(&mut scene, BuildOptions::default(), &SequentialExecutor);
+        let commands = output.commands;
+        assert_eq!(commands.len(), 2, "{:?}", commands);
+        assert!(matches!(commands[0], RenderCommand::Start { path_count: 0, .. }));
+        assert!(matches!(commands[1], RenderCommand::Finish { .. }));
+    }
+
+    #[cfg(feature="d3d9")]
+    #[test]
+    fn test_additive_blending_disables_occlusion_culling() {
+        // Returns the Z-buffer value at tile (1, 1), where two opaque rects overlap. Tiles of paths
+        // with lower IDs than this value are skipped there.
+        fn z_value_in_overlap(blend_mode: BlendMode) -> i32 {
+            let mut scene = Scene::new();
+            scene.set_view_box(RectF::new(vec2f(0.0, 0.0), vec2f(64.0, 64.0)));
+            let red = scene.push_paint(&Paint::from_color(ColorU::new(255, 0, 0, 255)));
+            for &origin in &[-8.0, 8.0] {
+                let rect = RectF::new(vec2f(origin, origin), vec2f(48.0, 48.0));
+                let mut draw_path = DrawPath::new(Outline::from_rect(rect), red);
+                draw_path.set_blend_mode(blend_mode);
+                scene.push_draw_path(draw_path);
+            }
+
+            let output = build_d3d9(&mut scene, BuildOptions::default(), &SequentialExecutor);
+            let z_values: Vec<_> = output.batches().map(|batch| {
+                assert_eq!(batch.blend_mode, blend_mode);
+                *batch.z_buffer_data.get(vec2i(1, 1)).unwrap()
+            }).collect();
+            assert_eq!(z_values.len(), 1);
+            z_values[0]
+        }
+
+        // Under source-over, the front rect hides the one behind it.
+        assert_eq!(z_value_in_overlap(BlendMode::SrcOver), 1);
+        // Additive blending sums both rects, so neither may be culled.
+        assert_eq!(z_value_in_overlap(BlendMode::Lighter), 0);
+    }
+
+    #[cfg(feature="d3d9")]
+    #[test]
+    fn test_paths_behind_opaque_rect_are_not_tiled() {
+        let mut scene = Scene::new();
+        scene.set_view_box(RectF::new(vec2f(0.0, 0.0), vec2f(128.0, 128.0)));
+        let red = scene.push_paint(&Paint::from_color(ColorU::new(255, 0, 0, 255)));
+        let hidden_rect = RectF::new(vec2f(16.5, 16.5), vec2f(20.0, 20.0));
+        let partially_hidden_rect = RectF::new(vec2f(60.5, 60.5), vec2f(40.0, 40.0));
+        let occluder_rect = RectF::new(vec2f(-8.0, -8.0), vec2f(88.0, 88.0));
+        for &rect in &[hidden_rect, partially_hidden_rect, occluder_rect] {
+            scene.push_draw_path(DrawPath::new(Outline::from_rect(rect), red));
+        }
+
+        let output = build_d3d9(&mut scene, BuildOptions::default(), &SequentialExecutor);
+        let mut tile_counts = [0; 3];
+        for tile in output.tiles() {
+            tile_counts[tile.path_id.0 as usize] += 1;
+        }
+        assert_eq!(tile_counts[0], 0);
+        assert!(tile_counts[1] > 0);
+        assert!(tile_counts[2] > 0);
+    }
+
+    #[cfg(feature="d3d9")]
+    #[test]
+    fn test_opaque_paths_only_occlude_within_their_layer() {
+        let mut scene = Scene::new();
+        scene.set_view_box(RectF::new(vec2f(0.0, 0.0), vec2f(64.0, 64.0)));
+        let red = scene.push_paint(&Paint::from_color(ColorU::new(255, 0, 0, 255)));
+        let lower_rect = RectF::new(vec2f(16.0, 16.0), vec2f(16.0, 16.0));
+        scene.push_draw_path(DrawPath::new(Outline::from_rect(lower_rect), red));
+        let upper_rect = RectF::new(vec2f(-8.0, -8.0), vec2f(80.0, 80.0));
+        let mut upper_path = DrawPath::new(Outline::from_rect(upper_rect), red);
+        upper_path.set_layer(1);
+        scene.push_draw_path(upper_path);
+
+        let output = build_d3d9(&mut scene, BuildOptions::default(), &SequentialExecutor);
+
+        // The upper rect covers the lower one completely, but the lower one must still be tiled,
+        // and in its own batch, so that the upper rect's Z values can't hide it.
+        let batches: Vec<_> = output.batches().collect();
+        assert_eq!(batches.len(), 2);
+        assert!(batches[0].tiles.iter().all(|tile| tile.path_id == PathId(0)));
+        assert!(!batches[0].tiles.is_empty());
+        assert_eq!(*batches[0].z_buffer_data.get(vec2i(1, 1)).unwrap(), 0);
+        assert!(batches[1].tiles.iter().all(|tile| tile.path_id == PathId(1)));
+    }
+
+    #[cfg(feature="d3d9")]
+    #[test]
+    fn test_lower_layers_are_drawn_first() {
+        let mut scene = Scene::new();
+        scene.set_view_box(RectF::new(vec2f(0.0, 0.0), vec2f(64.0, 64.0)));
+        let red = scene.push_paint(&Paint::from_color(ColorU::new(255, 0, 0, 255)));
+        let rect = RectF::new(vec2f(16.0, 16.0), vec2f(32.0, 32.0));
+        let mut upper_path = DrawPath::new(Outline::from_rect(rect), red);
+        upper_path.set_layer(1);
+        scene.push_draw_path(upper_path);
+        scene.push_draw_path(DrawPath::new(Outline::from_rect(rect), red));
+
+        // The layer 0 path was pushed last, but it's drawn first, underneath the layer 1 path.
+        let output = build_d3d9(&mut scene, BuildOptions::default(), &SequentialExecutor);
+        let batches: Vec<_> = output.batches().collect();
+        assert_eq!(batches.len(), 2);
+        assert!(!batches[0].tiles.is_empty());
+        assert!(batches[0].tiles.iter().all(|tile| tile.path_id == PathId(1)));
+        assert!(!batches[1].tiles.is_empty());
+        assert!(batches[1].tiles.iter().all(|tile| tile.path_id == PathId(0)));
+    }
+
+    #[cfg(feature="d3d9")]
+    #[test]
+    fn test_snap_to_tiles_leaves_axis_aligned_shapes_solid() {
+        // Returns the number of fills and the number of tiles drawn.
+        fn fill_and_tile_counts(snap_to_tiles: bool) -> (usize, usize) {
+            let mut scene = Scene::new();
+            scene.set_view_box(RectF::new(vec2f(0.0, 0.0), vec2f(128.0, 128.0)));
+            let red = scene.push_paint(&Paint::from_color(ColorU::new(255, 0, 0, 255)));
+            for &rect in &[RectF::new(vec2f(5.5, 9.25), vec2f(50.0, 30.0)),
+                           RectF::new(vec2f(70.0, 60.75), vec2f(41.5, 55.0))] {
+                scene.push_draw_path(DrawPath::new(Outline::from_rect(rect), red));
+            }
+
+            let options = BuildOptions { snap_to_tiles, ..BuildOptions::default() };
+            let output = build_d3d9(&mut scene, options, &SequentialExecutor);
+            (output.fills().count(), output.tiles().count())
+        }
+
+        assert!(fill_and_tile_counts(false).0 > 0);
+        let (fill_count, tile_count) = fill_and_tile_counts(true);
+        assert_eq!(fill_count, 0);
+        assert!(tile_count > 0);
+    }
+
+    #[cfg(feature="d3d9")]
+    #[test]
+    fn test_exceeding_max_total_fills_abandons_build() {
+        // Returns the result of the build, the number of fills sent, the number of tiles drawn,
+        // and whether the build finished.
+        fn build(max_total_fills: Option<usize>) -> (Result<(), BuildError>, usize, usize, bool) {
+            let mut scene = Scene::new();
+            scene.set_view_box(RectF::new(vec2f(0.0, 0.0), vec2f(256.0, 256.0)));
+            let paint = scene.push_paint(&Paint::from_color(ColorU::new(0, 0, 255, 128)));
+            for index in 0..16 {
+                let mut outline = Outline::new();
+                let center = vec2f((index * 37 % 200) as f32 + 28.3, (index * 53 % 200) as f32);
+                let transform = Transform2F::from_translation(center) *
+                    Transform2F::from_scale(vec2f(24.0, 17.5));
+                let mut contour = Contour::new();
+                contour.push_ellipse(&transform);
+                outline.push_contour(contour);
+                scene.push_draw_path(DrawPath::new(outline, paint));
+            }
+
+            let options = BuildOptions { max_total_fills, ..BuildOptions::default() };
+            let output = build_d3d9(&mut scene, options, &SequentialExecutor);
+            let finished = output.commands.iter().any(|command| {
+                matches!(*command, RenderCommand::Finish { .. })
+            });
+            (output.result, output.fills().count(), output.tiles().count(), finished)
+        }
+
+        let (result, fill_count, tile_count, finished) = build(None);
+        assert_eq!(result, Ok(()));
+        assert!(fill_count > 100 && tile_count > 0 && finished);
+
+        let (result, fill_count, tile_count, finished) = build(Some(100));
+        assert_eq!(result, Err(BuildError::TooManyFills { max_total_fills: 100 }));
+        assert!(fill_count <= 100);
+        assert_eq!(tile_count, 0);
+        assert!(finished);
+
+        // A single path can exceed the limit by itself.
+        let (result, fill_count, _, _) = build(Some(4));
+        assert_eq!(result, Err(BuildError::TooManyFills { max_total_fills: 4 }));
+        assert!(fill_count <= 4);
+    }
+
+    // Builds a scene of many overlapping shapes and returns every fill and tile it produces.
+    #[cfg(feature="d3d9")]
+    fn build_fills_and_tiles<E>(executor: &E) -> (Vec<Fill>, Vec<TileObjectPrimitive>)
+                                where E: Executor {
+        let mut scene = Scene::new();
+        scene.set_view_box(RectF::new(vec2f(0.0, 0.0), vec2f(256.0, 256.0)));
+        for index in 0..32 {
+            let color = ColorU::new(index as u8 * 8, 0, 255 - index as u8 * 8, 128);
+            let paint = scene.push_paint(&Paint::from_color(color));
+            let origin = vec2f((index * 37 % 200) as f32 + 0.3, (index * 53 % 200) as f32 + 0.7);
+            let mut contour = Contour::new();
+            contour.push_endpoint(origin);
+            contour.push_endpoint(origin + vec2f(60.0, 10.5));
+            contour.push_endpoint(origin + vec2f(20.25, 55.0));
+            contour.close();
+            let mut outline = Outline::new();
+            outline.push_contour(contour);
+            scene.push_draw_path(DrawPath::new(outline, paint));
+        }
+
+        let output = build_d3d9(&mut scene, BuildOptions::default(), executor);
+        (output.fills().cloned().collect(), output.tiles().cloned().collect())
+    }
+
+    // Describes each tile along with the fills of its alpha tile, independently of the order in
+    // which alpha tiles were allocated.
+    fn canonical_tiles(fills: &[Fill], tiles: &[TileObjectPrimitive]) -> Vec<String> {
+        tiles.iter().map(|tile| {
+            let tile_fills: Vec<_> = fills.iter().filter(|fill| {
+                !tile.is_solid() && fill.link == tile.alpha_tile_id.0
+            }).map(|fill| fill.line_segment).collect();
+            format!("{:?} ({}, {}) {} {} {} {:?}",
+                    tile.path_id,
+                    tile.tile_x,
+                    tile.tile_y,
+                    tile.backdrop,
+                    tile.ctrl,
+                    tile.color,
+                    tile_fills)
+        }).collect()
+    }
+
+    #[cfg(feature="d3d9")]
+    #[test]
+    fn test_sequential_builds_are_deterministic() {
+        let (fills_a, tiles_a) = build_fills_and_tiles(&SequentialExecutor);
+        let (fills_b, tiles_b) = build_fills_and_tiles(&SequentialExecutor);
+        assert!(!fills_a.is_empty());
+        assert_eq!(format!("{:?}", fills_a), format!("{:?}", fills_b));
+        assert_eq!(format!("{:?}", tiles_a), format!("{:?}", tiles_b));
+
+        // Parallel builds allocate alpha tiles and send fills in whatever order paths finish, but
+        // otherwise produce the same output.
+        let (parallel_fills, parallel_tiles) = build_fills_and_tiles(&RayonExecutor);
+        assert_eq!(canonical_tiles(&parallel_fills, &parallel_tiles),
+                   canonical_tiles(&fills_a, &tiles_a));
+    }
+}
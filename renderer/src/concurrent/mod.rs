@@ -13,3 +13,23 @@
 pub mod executor;
 pub mod rayon;
 pub mod scene_proxy;
+
+use crate::builder::BuiltPath;
+use crate::gpu_data::RenderCommand;
+use crate::paint::Paint;
+use crate::scene::{ClipPath, DrawPath, Scene};
+use crate::tiles::TilingPathInfo;
+
+// Fails to compile if any of the given types stops being `Send + Sync`.
+macro_rules! assert_send_sync {
+    ($($ty:ty),*) => {
+        const _: fn() = || {
+            fn assert_send_sync<T>() where T: Send + Sync {}
+            $(assert_send_sync::<$ty>();)*
+        };
+    }
+}
+
+// Executors run tiling jobs that all borrow the scene being built, and send each built path back
+// to the calling thread. `SceneProxy` also moves whole scenes and render commands across threads.
+assert_send_sync!(Scene, DrawPath, ClipPath, Paint, TilingPathInfo, BuiltPath, RenderCommand);
@@ -38,7 +38,7 @@ use pathfinder_resources::ResourceLoader;
 use pathfinder_simd::default::F32x2;
 use std::u32;
 
-const MAX_FILLS_PER_BATCH: usize = 0x10000;
+pub(crate) const MAX_FILLS_PER_BATCH: usize = 0x10000;
 
 pub(crate) struct RendererD3D9<D> where D: Device {
     // Basic data
@@ -24,6 +24,7 @@ use pathfinder_geometry::transform2d::Transform2F;
 use pathfinder_geometry::vector::{Vector2F, Vector2I};
 use pathfinder_gpu::TextureSamplingFlags;
 use std::fmt::{Debug, Formatter, Result as DebugResult};
+use std::iter;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::Duration;
@@ -228,6 +229,9 @@ pub struct DrawTileBatchD3D9 {
     pub tiles: Vec<TileObjectPrimitive>,
     pub clips: Vec<Clip>,
     pub z_buffer_data: DenseTileMap<i32>,
+    /// Every path touching each tile, in draw order, if requested via
+    /// `BuildOptions::record_tile_objects`.
+    pub tile_objects: Option<TileObjectLists>,
     /// The color texture to use.
     pub color_texture: Option<TileBatchTexture>,
     /// The filter to use.
@@ -236,6 +240,24 @@ pub struct DrawTileBatchD3D9 {
     pub blend_mode: BlendMode,
 }
 
+/// Per-tile lists of the paths touching each tile of a batch, in draw order.
+///
+/// Unlike the Z-buffer, which only remembers the topmost opaque path, this keeps every path so
+/// that transparent paths below the top can still be composited. The lists are packed into a
+/// single vector and linked by index.
+#[derive(Clone, Debug)]
+pub struct TileObjectLists {
+    first_entries: DenseTileMap<u32>,
+    last_entries: DenseTileMap<u32>,
+    entries: Vec<TileObjectListEntry>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct TileObjectListEntry {
+    path_id: PathId,
+    next: u32,
+}
+
 /// Information needed to draw a batch of tiles in D3D11.
 #[derive(Clone, Debug)]
 pub struct DrawTileBatchD3D11 {
@@ -444,6 +466,45 @@ impl PathBatchIndex {
     }
 }
 
+impl TileObjectLists {
+    pub fn new(tile_rect: RectI) -> TileObjectLists {
+        TileObjectLists {
+            first_entries: DenseTileMap::from_builder(|_| !0, tile_rect),
+            last_entries: DenseTileMap::from_builder(|_| !0, tile_rect),
+            entries: vec![],
+        }
+    }
+
+    /// Appends a path to the list of the tile at the given coordinates. Tiles outside the batch
+    /// are ignored.
+    pub fn push(&mut self, tile_coords: Vector2I, path_id: PathId) {
+        let tile_index = match self.first_entries.coords_to_index(tile_coords) {
+            None => return,
+            Some(tile_index) => tile_index,
+        };
+
+        let entry_index = self.entries.len() as u32;
+        self.entries.push(TileObjectListEntry { path_id, next: !0 });
+
+        let last_entry_index = self.last_entries.data[tile_index];
+        if last_entry_index == !0 {
+            self.first_entries.data[tile_index] = entry_index;
+        } else {
+            self.entries[last_entry_index as usize].next = entry_index;
+        }
+        self.last_entries.data[tile_index] = entry_index;
+    }
+
+    /// Returns the paths touching the tile at the given coordinates, in draw order.
+    pub fn get(&self, tile_coords: Vector2I) -> impl Iterator<Item = PathId> + '_ {
+        let first_entry_index = self.first_entries.get(tile_coords).cloned().unwrap_or(!0);
+        let entry_index = Some(first_entry_index).filter(|&index| index != !0);
+        iter::successors(entry_index, move |&index| {
+            Some(self.entries[index as usize].next).filter(|&next| next != !0)
+        }).map(move |index| self.entries[index as usize].path_id)
+    }
+}
+
 impl AlphaTileId {
     #[inline]
     pub fn new(next_alpha_tile_index: &[AtomicUsize; ALPHA_TILE_LEVEL_COUNT], level: usize) 
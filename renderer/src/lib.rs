@@ -22,6 +22,8 @@ pub mod gpu;
 pub mod options;
 pub mod paint;
 pub mod scene;
+#[cfg(feature="d3d9")]
+pub mod validate;
 
 mod allocator;
 mod builder;
@@ -16,10 +16,15 @@ use pathfinder_geometry::rect::RectF;
 use pathfinder_geometry::transform2d::Transform2F;
 use pathfinder_geometry::transform3d::Perspective;
 use pathfinder_geometry::vector::{Vector2F, Vector4F};
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
 
 #[allow(deprecated)]
 use pathfinder_content::clip::PolygonClipper3D;
 
+/// The default value of `BuildOptions::flattening_tolerance`.
+pub const DEFAULT_FLATTENING_TOLERANCE: f32 = 0.25;
+
 /// A sink for the render commands that scenes build.
 /// 
 /// In single-threaded operation, this object typically buffers commands into an array and then,
@@ -49,7 +54,7 @@ impl<'a> RenderCommandListener<'a> {
 }
 
 /// Options that influence scene building.
-#[derive(Clone, Default)]
+#[derive(Clone)]
 pub struct BuildOptions {
     /// A global transform to be applied to the scene.
     pub transform: RenderTransform,
@@ -58,6 +63,40 @@ pub struct BuildOptions {
     pub dilation: Vector2F,
     /// True if subpixel antialiasing for LCD screens is to be performed.
     pub subpixel_aa_enabled: bool,
+    /// True if outlines are to be clipped to the view box (padded by one tile) before tiling, so
+    /// that the tiler never sees geometry far outside the visible area. This is on by default.
+    pub clip_to_view_box: bool,
+    /// True if D3D9 tile batches are to record every path touching each tile, in draw order,
+    /// for use by transparency passes. This is off by default.
+    pub record_tile_objects: bool,
+    /// True if every outline point is to be moved to the nearest tile corner before tiling, so
+    /// that axis-aligned shapes cover whole tiles and need no masks. This makes the output blocky
+    /// and is off by default. It is only honored when paths are tiled on the CPU.
+    pub snap_to_tiles: bool,
+    /// The most fills the whole scene may generate. If tiling exceeds this, the build stops and
+    /// reports `BuildError::TooManyFills` instead of exhausting memory on pathological input.
+    /// This is unlimited by default. It is only honored when paths are tiled on the CPU.
+    pub max_total_fills: Option<usize>,
+    /// How far, in device pixels, the line segments that curves are approximated with may stray
+    /// from them. Lower values give smoother curves at the cost of more fills. It is only honored
+    /// when paths are tiled on the CPU.
+    pub flattening_tolerance: f32,
+}
+
+impl Default for BuildOptions {
+    #[inline]
+    fn default() -> BuildOptions {
+        BuildOptions {
+            transform: RenderTransform::default(),
+            dilation: Vector2F::zero(),
+            subpixel_aa_enabled: false,
+            clip_to_view_box: true,
+            record_tile_objects: false,
+            snap_to_tiles: false,
+            max_total_fills: None,
+            flattening_tolerance: DEFAULT_FLATTENING_TOLERANCE,
+        }
+    }
 }
 
 impl BuildOptions {
@@ -66,10 +105,37 @@ impl BuildOptions {
             transform: self.transform.prepare(bounds),
             dilation: self.dilation,
             subpixel_aa_enabled: self.subpixel_aa_enabled,
+            clip_to_view_box: self.clip_to_view_box,
+            record_tile_objects: self.record_tile_objects,
+            snap_to_tiles: self.snap_to_tiles,
+            max_total_fills: self.max_total_fills,
+            flattening_tolerance: self.flattening_tolerance,
+        }
+    }
+}
+
+/// A reason that building a scene was abandoned.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum BuildError {
+    /// Tiling generated more fills than `BuildOptions::max_total_fills` allows.
+    TooManyFills {
+        /// The limit that was exceeded.
+        max_total_fills: usize,
+    },
+}
+
+impl Display for BuildError {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match *self {
+            BuildError::TooManyFills { max_total_fills } => {
+                write!(formatter, "scene needs more than the maximum of {} fills", max_total_fills)
+            }
         }
     }
 }
 
+impl Error for BuildError {}
+
 /// A global transform to apply to the scene.
 #[derive(Clone)]
 pub enum RenderTransform {
@@ -143,6 +209,11 @@ pub(crate) struct PreparedBuildOptions {
     pub(crate) transform: PreparedRenderTransform,
     pub(crate) dilation: Vector2F,
     pub(crate) subpixel_aa_enabled: bool,
+    pub(crate) clip_to_view_box: bool,
+    pub(crate) record_tile_objects: bool,
+    pub(crate) snap_to_tiles: bool,
+    pub(crate) max_total_fills: Option<usize>,
+    pub(crate) flattening_tolerance: f32,
 }
 
 #[derive(Clone, Copy)]
@@ -200,4 +271,9 @@ impl PreparedRenderTransform {
             _ => false,
         }
     }
+
+    #[inline]
+    pub(crate) fn is_perspective(&self) -> bool {
+        matches!(*self, PreparedRenderTransform::Perspective { .. })
+    }
 }
@@ -15,16 +15,21 @@ use crate::concurrent::executor::Executor;
 use crate::gpu::options::RendererLevel;
 use crate::gpu::renderer::Renderer;
 use crate::gpu_data::RenderCommand;
-use crate::options::{BuildOptions, PreparedBuildOptions};
-use crate::options::{PreparedRenderTransform, RenderCommandListener};
+use crate::options::{BuildError, BuildOptions, DEFAULT_FLATTENING_TOLERANCE};
+use crate::options::{PreparedBuildOptions, PreparedRenderTransform, RenderCommandListener};
 use crate::paint::{MergedPaletteInfo, Paint, PaintId, PaintInfo, PaintTextureManager, Palette};
+use crate::tiles::{TILE_HEIGHT, TILE_WIDTH};
+#[cfg(feature="d3d9")]
+use crate::validate::{self, ValidationError};
+use pathfinder_color::ColorU;
 use pathfinder_content::effects::BlendMode;
 use pathfinder_content::fill::FillRule;
 use pathfinder_content::outline::Outline;
 use pathfinder_content::render_target::RenderTargetId;
+use pathfinder_content::stroke::{OutlineStrokeToFill, StrokeStyle};
 use pathfinder_geometry::rect::RectF;
 use pathfinder_geometry::transform2d::Transform2F;
-use pathfinder_geometry::vector::{Vector2I, vec2f};
+use pathfinder_geometry::vector::{Vector2F, Vector2I, vec2f, vec2i};
 use pathfinder_gpu::Device;
 use std::mem;
 use std::ops::Range;
@@ -150,6 +155,7 @@ impl Scene {
                 }),
                 fill_rule: draw_path.fill_rule,
                 blend_mode: draw_path.blend_mode,
+                layer: draw_path.layer,
                 name: draw_path.name,
             });
         }
@@ -224,6 +230,64 @@ impl Scene {
         self.epoch.next();
     }
 
+    /// Returns the union of the bounds of all draw paths in the scene.
+    ///
+    /// Unlike the view box, which the content declares (e.g. via an SVG `viewBox`), this covers
+    /// wherever the paths actually lie, including any content that overflows the view box. Returns
+    /// an empty rectangle at the origin if there are no draw paths.
+    pub fn content_bounds(&self) -> RectF {
+        let mut bounds = self.draw_paths.iter().map(|draw_path| draw_path.outline.bounds());
+        let first_bounds = bounds.next().unwrap_or_default();
+        bounds.fold(first_bounds, |bounds, path_bounds| bounds.union_rect(path_bounds))
+    }
+
+    /// Returns the color of the bottom-most path if it is an opaque, solid-colored rectangle
+    /// covering the entire view box.
+    ///
+    /// Many exported SVGs begin with such a background rectangle. A consumer can clear to this
+    /// color instead of drawing that path.
+    pub fn background_color(&self) -> Option<ColorU> {
+        let draw_path = match self.display_list.first() {
+            Some(DisplayItem::DrawPaths(range)) if range.start.0 < range.end.0 => {
+                self.get_draw_path(range.start)
+            }
+            _ => return None,
+        };
+        if draw_path.clip_path.is_some() || draw_path.blend_mode != BlendMode::SrcOver {
+            return None;
+        }
+
+        let paint = self.get_paint(draw_path.paint);
+        if !paint.is_color() || !paint.base_color().is_opaque() {
+            return None;
+        }
+
+        match draw_path.outline.as_axis_aligned_rect() {
+            Some(rect) if rect.contains_rect(self.view_box) => Some(paint.base_color()),
+            _ => None,
+        }
+    }
+
+    /// Replaces the outline of every draw path with thin strokes that trace the edges of its
+    /// flattened contours.
+    ///
+    /// This is a debugging aid: it shows the exact polylines that the tiler operates on. Clip
+    /// paths are left untouched.
+    pub fn convert_to_wireframe(&mut self, line_width: f32) {
+        let stroke_style = StrokeStyle { line_width, ..StrokeStyle::default() };
+
+        for draw_path in &mut self.draw_paths {
+            let flattened_outline = draw_path.outline.flattened(DEFAULT_FLATTENING_TOLERANCE);
+            let mut stroke_to_fill = OutlineStrokeToFill::new(&flattened_outline, stroke_style);
+            stroke_to_fill.offset();
+            draw_path.outline = stroke_to_fill.into_outline();
+            draw_path.fill_rule = FillRule::Winding;
+            self.bounds = self.bounds.union_rect(draw_path.outline.bounds());
+        }
+
+        self.epoch.next();
+    }
+
     #[allow(deprecated)]
     pub(crate) fn apply_render_options(&self,
                                        original_outline: &Outline,
@@ -269,6 +333,19 @@ impl Scene {
             outline.dilate(options.dilation);
         }
 
+        if options.clip_to_view_box && !options.transform.is_perspective() {
+            // Pad by a tile so that the clipped edges fall outside the tile grid. Otherwise an edge
+            // lying on the top of the view box would turn the first row of solid tiles into mask
+            // tiles.
+            let tile_size = vec2f(TILE_WIDTH as f32, TILE_HEIGHT as f32);
+            let clip_rect = self.effective_view_box(options).dilate(tile_size);
+            outline.clip_against_rect(clip_rect);
+        }
+
+        if options.snap_to_tiles {
+            outline.snap_to_grid(vec2f(TILE_WIDTH as f32, TILE_HEIGHT as f32));
+        }
+
         outline
     }
 
@@ -286,12 +363,29 @@ impl Scene {
     /// The given executor will be used to prepare these commands. Typically, this will be a
     /// `SequentialExecutor` to prepare commands on a single thread or `RayonExecutor` to prepare
     /// commands in parallel across multiple threads.
+    ///
+    /// If the build is abandoned, the error is logged. Use `try_build()` to handle it instead.
     #[inline]
     pub fn build<'a, 'b, E>(&mut self,
                             options: BuildOptions,
                             sink: &'b mut SceneSink<'a>,
                             executor: &E)
                             where E: Executor {
+        if let Err(err) = self.try_build(options, sink, executor) {
+            error!("failed to build scene: {}", err);
+        }
+    }
+
+    /// Like `build()`, but returns an error if building was abandoned, for example because it
+    /// exceeded `BuildOptions::max_total_fills`.
+    ///
+    /// An abandoned build still sends the `Start` and `Finish` commands but draws no tiles.
+    pub fn try_build<'a, 'b, E>(&mut self,
+                                options: BuildOptions,
+                                sink: &'b mut SceneSink<'a>,
+                                executor: &E)
+                                -> Result<(), BuildError>
+                                where E: Executor {
         let prepared_options = options.prepare(self.bounds);
         SceneBuilder::new(self, &prepared_options, sink).build(executor)
     }
@@ -353,15 +447,73 @@ impl Scene {
                                    executor: E)
                                    -> Vec<RenderCommand>
                                    where D: Device, E: Executor {
-        let commands = Arc::new(Mutex::new(vec![]));
-        let commands_for_listener = commands.clone();
-        let listener = RenderCommandListener::new(Box::new(move |command| {
-            commands_for_listener.lock().unwrap().push(command)
-        }));
-        let mut sink = SceneSink::new(listener, renderer.mode().level);
-        self.build(build_options, &mut sink, &executor);
-        let mut commands = commands.lock().unwrap();
-        mem::replace(&mut *commands, vec![])
+        let level = renderer.mode().level;
+        let ((), commands) = collect_render_commands(level, |sink| {
+            self.build(build_options, sink, &executor)
+        });
+        commands
+    }
+
+    /// Builds the scene on the CPU and collects its solid tiles, from which a simple backend can
+    /// derive triangles, per-paint groups, or spans without building again.
+    ///
+    /// Mask tiles, and tiles that the even-odd rule leaves empty, are skipped; a backend wanting
+    /// antialiased edges must render mask tiles separately.
+    #[cfg(feature="d3d9")]
+    pub fn build_solid_tiles<E>(&mut self, build_options: BuildOptions, executor: &E)
+                                -> SolidTiles
+                                where E: Executor {
+        let ((), commands) = collect_render_commands(RendererLevel::D3D9, |sink| {
+            self.build(build_options, sink, executor)
+        });
+
+        let tiles = commands.into_iter().flat_map(|command| {
+            match command {
+                RenderCommand::DrawTilesD3D9(batch) => batch.tiles,
+                _ => vec![],
+            }
+        }).filter(|tile| tile.is_filled());
+        SolidTiles {
+            tiles: tiles.map(|tile| {
+                let paint = PaintId(tile.color);
+                SolidTile {
+                    tile_coords: vec2i(tile.tile_x as i32, tile.tile_y as i32),
+                    paint,
+                    color: self.get_paint(paint).base_color(),
+                }
+            }).collect(),
+        }
+    }
+
+    /// Builds the scene on the CPU for the D3D9 renderer and checks the resulting render commands
+    /// with `validate::validate_d3d9_commands()`.
+    #[cfg(feature="d3d9")]
+    pub fn build_and_validate<E>(&mut self, build_options: BuildOptions, executor: &E)
+                                 -> Result<(), ValidationError>
+                                 where E: Executor {
+        let ((), commands) = collect_render_commands(RendererLevel::D3D9, |sink| {
+            self.build(build_options, sink, executor)
+        });
+        validate::validate_d3d9_commands(&commands, self.view_box)
+    }
+
+    /// Builds the scene on the CPU and returns how many tiles each draw path covers, indexed by
+    /// draw path ID, for level-of-detail decisions and tile budgeting.
+    ///
+    /// Paths culled behind opaque rectangles, and all paths of an abandoned build, count no
+    /// tiles.
+    #[cfg(feature="d3d9")]
+    pub fn build_path_tile_counts<E>(&mut self, build_options: BuildOptions, executor: &E)
+                                     -> Vec<PathTileCounts>
+                                     where E: Executor {
+        let listener = RenderCommandListener::new(Box::new(|_| {}));
+        let mut sink = SceneSink::new(listener, RendererLevel::D3D9);
+        let prepared_options = build_options.prepare(self.bounds);
+        let mut scene_builder = SceneBuilder::new(self, &prepared_options, &mut sink);
+        match scene_builder.build(executor) {
+            Ok(()) => scene_builder.draw_path_tile_counts,
+            Err(_) => vec![PathTileCounts::default(); self.draw_paths.len()],
+        }
     }
 
     /// A convenience method to build a scene and send the resulting commands to the given
@@ -378,6 +530,124 @@ impl Scene {
     }
 }
 
+// Calls `build` with a scene sink for a renderer of the given level, and returns its result along
+// with every command sent to the sink.
+pub(crate) fn collect_render_commands<F, T>(renderer_level: RendererLevel, build: F)
+                                            -> (T, Vec<RenderCommand>)
+                                            where F: FnOnce(&mut SceneSink) -> T {
+    let commands = Arc::new(Mutex::new(vec![]));
+    let commands_for_listener = commands.clone();
+    let listener = RenderCommandListener::new(Box::new(move |command| {
+        commands_for_listener.lock().unwrap().push(command)
+    }));
+    let mut sink = SceneSink::new(listener, renderer_level);
+    let result = build(&mut sink);
+    let mut commands = commands.lock().unwrap();
+    (result, mem::take(&mut *commands))
+}
+
+/// The solid tiles of a scene, in paint order, as produced by `Scene::build_solid_tiles()`.
+#[derive(Clone, Debug)]
+pub struct SolidTiles {
+    tiles: Vec<SolidTile>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct SolidTile {
+    tile_coords: Vector2I,
+    paint: PaintId,
+    color: ColorU,
+}
+
+impl SolidTiles {
+    /// Tessellates every solid tile into two triangles, for simple backends that can only draw
+    /// colored triangles.
+    ///
+    /// Six vertices are returned per tile, in paint order, so drawing them in sequence reproduces
+    /// the solid coverage of the scene. Paints other than solid colors are approximated by their
+    /// base color.
+    pub fn triangles(&self) -> Vec<SolidTileVertex> {
+        let tile_size = vec2f(TILE_WIDTH as f32, TILE_HEIGHT as f32);
+        let mut vertices = vec![];
+        for tile in &self.tiles {
+            let rect = RectF::new(tile.tile_coords.to_f32() * tile_size, tile_size);
+            for &position in &[rect.origin(), rect.upper_right(), rect.lower_right(),
+                               rect.origin(), rect.lower_right(), rect.lower_left()] {
+                vertices.push(SolidTileVertex { position, color: tile.color });
+            }
+        }
+        vertices
+    }
+
+    /// Groups the coordinates of every solid tile by paint, so that a backend can draw all solid
+    /// tiles sharing a paint with one call.
+    ///
+    /// Groups appear in the order their paints are first used, and tiles within a group appear
+    /// in paint order.
+    pub fn by_paint(&self) -> Vec<(PaintId, Vec<Vector2I>)> {
+        let mut groups: Vec<(PaintId, Vec<Vector2I>)> = vec![];
+        for tile in &self.tiles {
+            match groups.iter_mut().find(|(group_paint, _)| *group_paint == tile.paint) {
+                Some((_, group_tiles)) => group_tiles.push(tile.tile_coords),
+                None => groups.push((tile.paint, vec![tile.tile_coords])),
+            }
+        }
+        groups
+    }
+
+    /// Coalesces runs of horizontally adjacent solid tiles that share a paint into spans, so that
+    /// a backend can draw each run as a single rectangle.
+    ///
+    /// Spans appear in paint order, so drawing them in sequence reproduces the solid coverage of
+    /// the scene. Mask tiles break runs.
+    pub fn spans(&self) -> Vec<SolidTileSpan> {
+        let mut spans: Vec<SolidTileSpan> = vec![];
+        for tile in &self.tiles {
+            let (tile_x, tile_y) = (tile.tile_coords.x(), tile.tile_coords.y());
+            if let Some(span) = spans.last_mut() {
+                if span.paint == tile.paint && span.tile_y == tile_y && span.tile_x.end == tile_x {
+                    span.tile_x.end += 1;
+                    continue;
+                }
+            }
+            spans.push(SolidTileSpan { tile_y, tile_x: tile_x..(tile_x + 1), paint: tile.paint });
+        }
+        spans
+    }
+}
+
+/// A vertex of a solid tile triangle, as produced by `SolidTiles::triangles()`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SolidTileVertex {
+    /// The position of this vertex in device pixels, after the build transform. Vertices lie on
+    /// tile corners.
+    pub position: Vector2F,
+    /// The color of the tile this vertex belongs to.
+    pub color: ColorU,
+}
+
+/// How many tiles a draw path covers, as produced by `Scene::build_path_tile_counts()`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PathTileCounts {
+    /// The number of tiles in the path's tile rect.
+    pub tiles: usize,
+    /// The number of tiles that the path covers completely, without needing a mask.
+    pub solid_tiles: usize,
+    /// The number of tiles that the path covers partially and that thus need a mask.
+    pub mask_tiles: usize,
+}
+
+/// A horizontal run of solid tiles with the same paint, as produced by `SolidTiles::spans()`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SolidTileSpan {
+    /// The row of tiles that this span lies in.
+    pub tile_y: i32,
+    /// The columns of the tiles that this span covers.
+    pub tile_x: Range<i32>,
+    /// The paint of every tile in this span.
+    pub paint: PaintId,
+}
+
 /// Receives render commands and delivers them to a `RenderCommandListener`.
 ///
 /// Scene sinks wrap render command listeners with cached information about the previous scene.
@@ -449,6 +719,9 @@ pub struct DrawPath {
     pub fill_rule: FillRule,
     /// How to blend this path with everything below it.
     pub blend_mode: BlendMode,
+    /// The layer this path belongs to. Layers are drawn in ascending order, and paths only occlude
+    /// other paths in the same layer.
+    pub layer: u8,
     /// The name of this path, for debugging.
     ///
     /// Pass the empty string (which does not allocate) if debugging is not needed.
@@ -512,7 +785,7 @@ impl DrawPath {
     /// Creates a new draw path with the given outline and paint.
     ///
     /// Initially, there is no clip path, the fill rule is set to winding, the blend mode is set to
-    /// source-over, and the path has no name.
+    /// source-over, the path is in layer 0, and the path has no name.
     #[inline]
     pub fn new(outline: Outline, paint: PaintId) -> DrawPath {
         DrawPath {
@@ -521,6 +794,7 @@ impl DrawPath {
             clip_path: None,
             fill_rule: FillRule::Winding,
             blend_mode: BlendMode::SrcOver,
+            layer: 0,
             name: String::new(),
         }
     }
@@ -545,8 +819,9 @@ impl DrawPath {
         self.clip_path = new_clip_path
     }
 
+    /// Returns the ID of the paint used to fill this path.
     #[inline]
-    pub(crate) fn paint(&self) -> PaintId {
+    pub fn paint(&self) -> PaintId {
         self.paint
     }
 
@@ -573,11 +848,36 @@ impl DrawPath {
         self.blend_mode = new_blend_mode
     }
 
+    #[inline]
+    pub(crate) fn layer(&self) -> u8 {
+        self.layer
+    }
+
+    /// Moves this path to a different layer.
+    ///
+    /// Layers are composited in ascending order, so this path is drawn over every path in a lower
+    /// layer, whenever either was pushed. Within a layer, paths are drawn in the order they were
+    /// pushed. Opaque paths only occlude paths in the same layer, so content in one layer can be
+    /// updated without invalidating culling decisions made for another.
+    #[inline]
+    pub fn set_layer(&mut self, new_layer: u8) {
+        self.layer = new_layer
+    }
+
     /// Assigns a name to this path, for debugging.
     #[inline]
     pub fn set_name(&mut self, new_name: String) {
         self.name = new_name
     }
+
+    /// Returns the name of this path.
+    ///
+    /// Built tiles record the ID of the path they belong to, so this can be used to map rendered
+    /// tiles back to the objects they came from (for instance, the SVG element).
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
 }
 
 impl ClipPath {
@@ -665,8 +965,311 @@ impl PathId {
         ClipPathId(self.0)
     }
 
+    /// Converts the path ID of a built draw path tile back into the ID of its draw path.
     #[inline]
-    pub(crate) fn to_draw_path_id(self) -> DrawPathId {
+    pub fn to_draw_path_id(self) -> DrawPathId {
         DrawPathId(self.0)
     }
 }
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::{DrawPath, DrawPathId, PathTileCounts, Scene, SolidTileSpan};
+    use super::collect_render_commands;
+    use crate::concurrent::executor::{Executor, SequentialExecutor};
+    use crate::gpu::options::RendererLevel;
+    use crate::gpu_data::{DrawTileBatchD3D9, Fill, RenderCommand, TileObjectPrimitive};
+    use crate::options::{BuildError, BuildOptions};
+    use crate::paint::Paint;
+    use pathfinder_color::ColorU;
+    use pathfinder_content::fill::FillRule;
+    use pathfinder_content::outline::{Contour, Outline};
+    use pathfinder_geometry::rect::RectF;
+    use pathfinder_geometry::vector::{Vector2F, vec2f, vec2i};
+
+    // The result of a D3D9 build and every command it sent.
+    #[cfg(feature="d3d9")]
+    pub(crate) struct D3D9Output {
+        pub(crate) result: Result<(), BuildError>,
+        pub(crate) commands: Vec<RenderCommand>,
+    }
+
+    #[cfg(feature="d3d9")]
+    impl D3D9Output {
+        pub(crate) fn fills(&self) -> impl Iterator<Item = &Fill> {
+            self.commands.iter().flat_map(|command| {
+                match *command {
+                    RenderCommand::AddFillsD3D9(ref fills) => &fills[..],
+                    _ => &[],
+                }
+            })
+        }
+
+        pub(crate) fn batches(&self) -> impl Iterator<Item = &DrawTileBatchD3D9> {
+            self.commands.iter().filter_map(|command| {
+                match *command {
+                    RenderCommand::DrawTilesD3D9(ref batch) => Some(batch),
+                    _ => None,
+                }
+            })
+        }
+
+        pub(crate) fn tiles(&self) -> impl Iterator<Item = &TileObjectPrimitive> {
+            self.batches().flat_map(|batch| batch.tiles.iter())
+        }
+    }
+
+    // Builds the scene for the D3D9 renderer and collects what it sends.
+    #[cfg(feature="d3d9")]
+    pub(crate) fn build_d3d9<E>(scene: &mut Scene, options: BuildOptions, executor: &E)
+                                -> D3D9Output
+                                where E: Executor {
+        let (result, commands) = collect_render_commands(RendererLevel::D3D9, |sink| {
+            scene.try_build(options, sink, executor)
+        });
+        D3D9Output { result, commands }
+    }
+
+    #[test]
+    fn test_wireframe_traces_edges() {
+        let mut scene = Scene::new();
+        let paint = scene.push_paint(&Paint::from_color(ColorU::black()));
+        let square = RectF::new(vec2f(10.0, 10.0), vec2f(20.0, 20.0));
+        scene.push_draw_path(DrawPath::new(Outline::from_rect(square), paint));
+
+        scene.convert_to_wireframe(2.0);
+
+        let outline = scene.get_draw_path(DrawPathId(0)).outline();
+        assert_eq!(outline.bounds(), square.dilate(1.0));
+
+        // All four edges are covered...
+        for &edge_point in &[vec2f(20.0, 10.0), vec2f(30.0, 20.0), vec2f(20.0, 30.0),
+                             vec2f(10.0, 20.0)] {
+            assert_eq!(outline.coverage_at(edge_point, FillRule::Winding), 1.0);
+        }
+        // ...but the interior is not.
+        assert_eq!(outline.coverage_at(vec2f(20.0, 20.0), FillRule::Winding), 0.0);
+    }
+
+    #[test]
+    fn test_outlines_are_clipped_to_view_box() {
+        let view_box = RectF::new(vec2f(0.0, 0.0), vec2f(64.0, 64.0));
+        let mut scene = Scene::new();
+        scene.set_view_box(view_box);
+
+        let mut contour = Contour::new();
+        contour.push_endpoint(vec2f(32.0, -40.0));
+        contour.push_endpoint(vec2f(120.0, 100.0));
+        contour.push_endpoint(vec2f(-50.0, 60.0));
+        contour.close();
+        let mut triangle = Outline::new();
+        triangle.push_contour(contour);
+
+        let options = BuildOptions::default().prepare(scene.bounds());
+        let clipped = scene.apply_render_options(&triangle, &options);
+        assert!(!clipped.is_empty());
+        assert!(view_box.dilate(16.001).contains_rect(clipped.bounds()));
+
+        let options = BuildOptions { clip_to_view_box: false, ..BuildOptions::default() };
+        let unclipped = scene.apply_render_options(&triangle, &options.prepare(scene.bounds()));
+        assert_eq!(unclipped.bounds(), triangle.bounds());
+    }
+
+    #[cfg(feature="d3d9")]
+    #[test]
+    fn test_solid_tile_triangles_of_single_tile() {
+        let mut scene = Scene::new();
+        scene.set_view_box(RectF::new(Vector2F::zero(), vec2f(16.0, 16.0)));
+        let color = ColorU::new(255, 128, 0, 255);
+        let paint = scene.push_paint(&Paint::from_color(color));
+        let rect = RectF::new(vec2f(-8.0, -8.0), vec2f(32.0, 32.0));
+        scene.push_draw_path(DrawPath::new(Outline::from_rect(rect), paint));
+
+        let vertices = scene.build_solid_tiles(BuildOptions::default(), &SequentialExecutor)
+                            .triangles();
+        assert_eq!(vertices.len(), 6);
+        assert!(vertices.iter().all(|vertex| vertex.color == color));
+        let positions: Vec<_> = vertices.iter().map(|vertex| vertex.position).collect();
+        assert_eq!(positions, vec![
+            vec2f(0.0, 0.0), vec2f(16.0, 0.0), vec2f(16.0, 16.0),
+            vec2f(0.0, 0.0), vec2f(16.0, 16.0), vec2f(0.0, 16.0),
+        ]);
+    }
+
+    #[cfg(feature="d3d9")]
+    #[test]
+    fn test_solid_tile_triangles_skip_tiles_empty_under_even_odd() {
+        // A ring of eight tiles around a hole whose backdrop is 2.
+        let mut scene = Scene::new();
+        scene.set_view_box(RectF::new(Vector2F::zero(), vec2f(48.0, 48.0)));
+        let paint = scene.push_paint(&Paint::from_color(ColorU::black()));
+        let mut outline = Outline::new();
+        outline.push_contour(Contour::from_rect(RectF::new(vec2f(0.0, 0.0), vec2f(48.0, 48.0))));
+        outline.push_contour(Contour::from_rect(RectF::new(vec2f(16.0, 16.0), vec2f(16.0, 16.0))));
+        let mut draw_path = DrawPath::new(outline, paint);
+        draw_path.set_fill_rule(FillRule::EvenOdd);
+        scene.push_draw_path(draw_path);
+
+        let vertices = scene.build_solid_tiles(BuildOptions::default(), &SequentialExecutor)
+                            .triangles();
+        assert_eq!(vertices.len(), 8 * 6);
+        let hole = RectF::new(vec2f(16.0, 16.0), vec2f(16.0, 16.0));
+        assert!(!vertices.chunks(6).any(|triangles| {
+            triangles.iter().all(|vertex| hole.contains_point(vertex.position))
+        }));
+    }
+
+    #[cfg(feature="d3d9")]
+    #[test]
+    fn test_solid_tiles_grouped_by_paint() {
+        let mut scene = Scene::new();
+        scene.set_view_box(RectF::new(Vector2F::zero(), vec2f(64.0, 16.0)));
+        let red = scene.push_paint(&Paint::from_color(ColorU::new(255, 0, 0, 255)));
+        let blue = scene.push_paint(&Paint::from_color(ColorU::new(0, 0, 255, 255)));
+        let left = RectF::new(vec2f(-8.0, -8.0), vec2f(40.0, 32.0));
+        let right = RectF::new(vec2f(32.0, -8.0), vec2f(40.0, 32.0));
+        scene.push_draw_path(DrawPath::new(Outline::from_rect(left), red));
+        scene.push_draw_path(DrawPath::new(Outline::from_rect(right), blue));
+
+        let groups = scene.build_solid_tiles(BuildOptions::default(), &SequentialExecutor)
+                          .by_paint();
+        assert_eq!(groups, vec![
+            (red, vec![vec2i(0, 0), vec2i(1, 0)]),
+            (blue, vec![vec2i(2, 0), vec2i(3, 0)]),
+        ]);
+    }
+
+    #[cfg(feature="d3d9")]
+    #[test]
+    fn test_path_tile_counts_respect_fill_rule() {
+        // Two nested tile-aligned squares wound the same way. The inner 4x4 tiles have a
+        // backdrop of 2, which fills them under the nonzero rule but not the even-odd rule.
+        fn tile_counts(fill_rule: FillRule) -> Vec<PathTileCounts> {
+            let mut scene = Scene::new();
+            scene.set_view_box(RectF::new(Vector2F::zero(), vec2f(128.0, 128.0)));
+            let paint = scene.push_paint(&Paint::from_color(ColorU::black()));
+            let mut outline = Outline::new();
+            outline.push_contour(Contour::from_rect(RectF::new(vec2f(0.0, 0.0),
+                                                               vec2f(128.0, 128.0))));
+            outline.push_contour(Contour::from_rect(RectF::new(vec2f(32.0, 32.0),
+                                                               vec2f(64.0, 64.0))));
+            let mut draw_path = DrawPath::new(outline, paint);
+            draw_path.set_fill_rule(fill_rule);
+            scene.push_draw_path(draw_path);
+            scene.build_path_tile_counts(BuildOptions::default(), &SequentialExecutor)
+        }
+
+        assert_eq!(tile_counts(FillRule::Winding),
+                   vec![PathTileCounts { tiles: 64, solid_tiles: 64, mask_tiles: 0 }]);
+        assert_eq!(tile_counts(FillRule::EvenOdd),
+                   vec![PathTileCounts { tiles: 64, solid_tiles: 48, mask_tiles: 0 }]);
+    }
+
+    #[cfg(feature="d3d9")]
+    #[test]
+    fn test_solid_tile_spans() {
+        let mut scene = Scene::new();
+        scene.set_view_box(RectF::new(Vector2F::zero(), vec2f(128.0, 16.0)));
+        let red = scene.push_paint(&Paint::from_color(ColorU::new(255, 0, 0, 255)));
+
+        // A row of five red tiles makes one span.
+        let five_tiles = RectF::new(vec2f(16.0, -8.0), vec2f(80.0, 32.0));
+        scene.push_draw_path(DrawPath::new(Outline::from_rect(five_tiles), red));
+        let spans = scene.build_solid_tiles(BuildOptions::default(), &SequentialExecutor).spans();
+        assert_eq!(spans, vec![SolidTileSpan { tile_y: 0, tile_x: 1..6, paint: red }]);
+
+        // Changing the paint of the last two splits the run. Tiles under later paths stay in
+        // the spans of earlier ones, so use two abutting rects.
+        let mut scene = Scene::new();
+        scene.set_view_box(RectF::new(Vector2F::zero(), vec2f(128.0, 16.0)));
+        let red = scene.push_paint(&Paint::from_color(ColorU::new(255, 0, 0, 255)));
+        let blue = scene.push_paint(&Paint::from_color(ColorU::new(0, 0, 255, 255)));
+        let three_tiles = RectF::new(vec2f(16.0, -8.0), vec2f(48.0, 32.0));
+        let two_tiles = RectF::new(vec2f(64.0, -8.0), vec2f(32.0, 32.0));
+        scene.push_draw_path(DrawPath::new(Outline::from_rect(three_tiles), red));
+        scene.push_draw_path(DrawPath::new(Outline::from_rect(two_tiles), blue));
+        let spans = scene.build_solid_tiles(BuildOptions::default(), &SequentialExecutor).spans();
+        assert_eq!(spans, vec![
+            SolidTileSpan { tile_y: 0, tile_x: 1..4, paint: red },
+            SolidTileSpan { tile_y: 0, tile_x: 4..6, paint: blue },
+        ]);
+    }
+
+    #[test]
+    fn test_background_color_of_full_canvas_rect() {
+        let view_box = RectF::new(vec2f(0.0, 0.0), vec2f(100.0, 50.0));
+        let mut scene = Scene::new();
+        scene.set_view_box(view_box);
+        let white = scene.push_paint(&Paint::from_color(ColorU::white()));
+        let black = scene.push_paint(&Paint::from_color(ColorU::black()));
+        scene.push_draw_path(DrawPath::new(Outline::from_rect(view_box), white));
+        let dot = RectF::new(vec2f(10.0, 10.0), vec2f(5.0, 5.0));
+        scene.push_draw_path(DrawPath::new(Outline::from_rect(dot), black));
+        assert_eq!(scene.background_color(), Some(ColorU::white()));
+
+        // A rect that leaves part of the view box uncovered isn't a background.
+        let mut scene = Scene::new();
+        scene.set_view_box(view_box);
+        let white = scene.push_paint(&Paint::from_color(ColorU::white()));
+        let inset = view_box.contract(1.0);
+        scene.push_draw_path(DrawPath::new(Outline::from_rect(inset), white));
+        assert_eq!(scene.background_color(), None);
+
+        // Nor is a translucent one.
+        let mut scene = Scene::new();
+        scene.set_view_box(view_box);
+        let translucent = scene.push_paint(&Paint::from_color(ColorU::new(255, 255, 255, 128)));
+        scene.push_draw_path(DrawPath::new(Outline::from_rect(view_box), translucent));
+        assert_eq!(scene.background_color(), None);
+    }
+
+    #[cfg(feature="d3d9")]
+    #[test]
+    fn test_built_tiles_map_back_to_path_names() {
+        let mut scene = Scene::new();
+        scene.set_view_box(RectF::new(vec2f(0.0, 0.0), vec2f(64.0, 64.0)));
+        let paint = scene.push_paint(&Paint::from_color(ColorU::black()));
+        for (name, origin) in vec![("back", 0.0), ("front", 24.0)] {
+            let rect = RectF::new(vec2f(origin, origin), vec2f(40.0, 40.0));
+            let mut draw_path = DrawPath::new(Outline::from_rect(rect), paint);
+            draw_path.set_name(name.to_owned());
+            scene.push_draw_path(draw_path);
+        }
+
+        let output = build_d3d9(&mut scene, BuildOptions::default(), &SequentialExecutor);
+        let tiles: Vec<_> = output.tiles().collect();
+        let names_at = |x, y| -> Vec<&str> {
+            tiles.iter().filter(|tile| (tile.tile_x, tile.tile_y) == (x, y)).map(|tile| {
+                scene.get_draw_path(tile.path_id.to_draw_path_id()).name()
+            }).collect()
+        };
+        assert_eq!(names_at(0, 0), vec!["back"]);
+        assert_eq!(names_at(3, 3), vec!["front"]);
+        assert_eq!(names_at(2, 2), vec!["back", "front"]);
+    }
+
+    #[cfg(feature="d3d9")]
+    #[test]
+    fn test_fitting_view_box_to_content_tiles_overflowing_content() {
+        // Returns the number of tiles drawn for a rect that overflows the declared view box.
+        fn tile_count(fit_content: bool) -> usize {
+            let mut scene = Scene::new();
+            scene.set_view_box(RectF::new(vec2f(0.0, 0.0), vec2f(64.0, 64.0)));
+            let paint = scene.push_paint(&Paint::from_color(ColorU::black()));
+            let rect = RectF::new(vec2f(32.0, 32.0), vec2f(80.0, 80.0));
+            scene.push_draw_path(DrawPath::new(Outline::from_rect(rect), paint));
+            assert_eq!(scene.content_bounds(), rect);
+            if fit_content {
+                let content_bounds = scene.content_bounds();
+                scene.set_view_box(content_bounds);
+            }
+
+            build_d3d9(&mut scene, BuildOptions::default(), &SequentialExecutor).tiles().count()
+        }
+
+        // The declared view box only shows the rect's upper left 2x2 tiles.
+        assert_eq!(tile_count(false), 4);
+        // The 80x80 rect spans 5x5 tiles.
+        assert_eq!(tile_count(true), 25);
+    }
+}
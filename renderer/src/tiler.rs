@@ -16,7 +16,7 @@ use crate::gpu::options::RendererLevel;
 use crate::gpu_data::AlphaTileId;
 use crate::options::PrepareMode;
 use crate::scene::{ClipPathId, PathId};
-use crate::tiles::{TILE_HEIGHT, TILE_WIDTH, TilingPathInfo};
+use crate::tiles::{self, TILE_HEIGHT, TILE_WIDTH, TilingPathInfo};
 use pathfinder_content::clip;
 use pathfinder_content::fill::FillRule;
 use pathfinder_content::outline::{ContourIterFlags, Outline};
@@ -27,7 +27,13 @@ use pathfinder_geometry::vector::{Vector2F, Vector2I, vec2f, vec2i};
 use pathfinder_simd::default::{F32x2, U32x2};
 use std::f32::NEG_INFINITY;
 
-const FLATTENING_TOLERANCE: f32 = 0.25;
+// Logs a tiling step if the `trace` feature is enabled. Compiles to nothing otherwise.
+macro_rules! trace_tiling {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "trace")]
+        trace!($($arg)*);
+    }
+}
 
 pub(crate) struct Tiler<'a, 'b, 'c, 'd> {
     scene_builder: &'a SceneBuilder<'b, 'a, 'c, 'd>,
@@ -83,6 +89,23 @@ impl<'a, 'b, 'c, 'd> Tiler<'a, 'b, 'c, 'd> {
     fn generate_fills(&mut self) {
         debug_assert_eq!(self.scene_builder.sink.renderer_level, RendererLevel::D3D9);
 
+        trace_tiling!("generate_fills(bounds={:?}, {} contours)",
+                      self.object_builder.bounds,
+                      self.outline.len());
+
+        // Rectangles are common enough (backgrounds, UI boxes, glyph stems) to warrant skipping
+        // segment iteration and curve handling. Their edges go straight to the line stepper.
+        if self.outline.as_axis_aligned_rect().is_some() {
+            trace_tiling!("... axis-aligned rect fast path");
+            let contour = &self.outline.contours()[0];
+            for point_index in 0..4 {
+                let edge = LineSegment2F::new(contour.position_of(point_index),
+                                              contour.position_of((point_index + 1) % 4));
+                process_line_segment(edge, self.scene_builder, &mut self.object_builder);
+            }
+            return;
+        }
+
         for contour in self.outline.contours() {
             for segment in contour.iter(ContourIterFlags::empty()) {
                 process_segment(&segment, self.scene_builder, &mut self.object_builder);
@@ -101,15 +124,26 @@ impl<'a, 'b, 'c, 'd> Tiler<'a, 'b, 'c, 'd> {
             }
         };
 
+        let fill_rule = self.object_builder.built_path.fill_rule;
+
         // Propagate backdrops.
         let tiles_across = tiles.rect.width() as usize;
         for (draw_tile_index, draw_tile) in tiles.data.iter_mut().enumerate() {
             let tile_coords = vec2i(draw_tile.tile_x as i32, draw_tile.tile_y as i32);
             let column = draw_tile_index % tiles_across;
-            let delta = draw_tile.backdrop as i32;
+            let delta = self.object_builder.backdrop_deltas[draw_tile_index];
 
             let mut draw_alpha_tile_id = draw_tile.alpha_tile_id;
-            let mut draw_tile_backdrop = backdrops[column] as i8;
+
+            // Under the nonzero rule a tile without a mask is fully covered whatever its winding
+            // number, so keep only the sign.
+            let backdrop = backdrops[column];
+            let mut draw_tile_backdrop =
+                if draw_alpha_tile_id == AlphaTileId(!0) && fill_rule == FillRule::Winding {
+                    backdrop.signum() as i8
+                } else {
+                    tiles::narrow_backdrop(backdrop, fill_rule)
+                };
 
             if let Some(built_clip_path) = self.clip_path {
                 let clip_tiles = match built_clip_path.data {
@@ -155,6 +189,11 @@ impl<'a, 'b, 'c, 'd> Tiler<'a, 'b, 'c, 'd> {
                 }
             }
 
+            trace_tiling!("prepare_tiles: tile {:?} alpha tile {:?} backdrop {}",
+                          tile_coords,
+                          draw_alpha_tile_id,
+                          draw_tile_backdrop);
+
             draw_tile.alpha_tile_id = draw_alpha_tile_id;
             draw_tile.backdrop = draw_tile_backdrop;
 
@@ -166,21 +205,11 @@ impl<'a, 'b, 'c, 'd> Tiler<'a, 'b, 'c, 'd> {
 fn process_segment(segment: &Segment,
                    scene_builder: &SceneBuilder,
                    object_builder: &mut ObjectBuilder) {
-    // TODO(pcwalton): Stop degree elevating.
-    if segment.is_quadratic() {
-        let cubic = segment.to_cubic();
-        return process_segment(&cubic, scene_builder, object_builder);
-    }
-
-    if segment.is_line() ||
-            (segment.is_cubic() && segment.as_cubic_segment().is_flat(FLATTENING_TOLERANCE)) {
-        return process_line_segment(segment.baseline, scene_builder, object_builder);
-    }
+    trace_tiling!("process_segment({:?})", segment);
 
     // TODO(pcwalton): Use a smarter flattening algorithm.
-    let (prev, next) = segment.split(0.5);
-    process_segment(&prev, scene_builder, object_builder);
-    process_segment(&next, scene_builder, object_builder);
+    let tolerance = scene_builder.built_options.flattening_tolerance;
+    segment.flatten(tolerance, |line| process_line_segment(line, scene_builder, object_builder));
 }
 
 // This is the meat of the technique. It implements the fast lattice-clipping algorithm from
@@ -199,9 +228,16 @@ fn process_line_segment(line_segment: LineSegment2F,
         Some(line_segment) => line_segment,
     };
 
+    trace_tiling!("process_line_segment({:?})", line_segment);
+
     let tile_size = vec2f(TILE_WIDTH as f32, TILE_HEIGHT as f32);
     let tile_size_recip = Vector2F::splat(1.0) / tile_size;
 
+    // Tiles are half-open: tile row `y` covers `[y * TILE_HEIGHT, (y + 1) * TILE_HEIGHT)`, so a
+    // point exactly on a tile boundary belongs to the tile below (or to the right of) it. Edges
+    // lying on a boundary therefore land in exactly one row, and a contour that merely touches a
+    // boundary from above enters and leaves the row below at the same point, so the auxiliary
+    // fills added there cancel out.
     let tile_line_segment =
         (line_segment.0 * tile_size_recip.0.concat_xy_xy(tile_size_recip.0)).floor().to_i32x4();
     let from_tile_coords = Vector2I(tile_line_segment.xy());
@@ -216,11 +252,19 @@ fn process_line_segment(line_segment: LineSegment2F,
 
     // Compute `first_tile_crossing = (from_tile_coords + vec2i(vector.x >= 0 ? 1 : 0,
     // vector.y >= 0 ? 1 : 0)) * tile_size`.
-    let first_tile_crossing = (from_tile_coords +
-        Vector2I((!vector_is_negative & U32x2::splat(1)).to_i32x2())).to_f32() * tile_size;
+    let crossing_offset = Vector2I((!vector_is_negative & U32x2::splat(1)).to_i32x2());
+    let first_tile_crossing = (from_tile_coords + crossing_offset).to_f32() * tile_size;
 
     let mut t_max = (first_tile_crossing - line_segment.from()) / vector;
-    let t_delta = (tile_size / vector).abs();
+    let mut t_delta = (tile_size / vector).abs();
+
+    // Vertical segments stay in one tile column, so they must never step in X. Dividing by their
+    // zero width usually yields infinity, which does the right thing, but it yields NaN if the
+    // crossing rounds onto the start point, and a NaN `t_max` corrupts every fill after it.
+    if vector.x() == 0.0 {
+        t_max.set_x(f32::INFINITY);
+        t_delta.set_x(f32::INFINITY);
+    }
 
     let (mut current_position, mut tile_coords) = (line_segment.from(), from_tile_coords);
     let mut last_step_direction = None;
@@ -257,8 +301,21 @@ fn process_line_segment(line_segment: LineSegment2F,
             Some(next_step_direction)
         };
 
-        let next_position = line_segment.sample(next_t);
+        // Accumulating `t_delta` drifts over long lines, so snap the crossing point onto the
+        // boundary being crossed. Otherwise the next tile's fills would start off its edge.
+        let mut next_position = line_segment.sample(next_t);
+        let tile_crossing = (tile_coords + crossing_offset).to_f32() * tile_size;
+        match next_step_direction {
+            Some(StepDirection::X) => next_position.set_x(tile_crossing.x()),
+            Some(StepDirection::Y) => next_position.set_y(tile_crossing.y()),
+            None => {}
+        }
+
         let clipped_line_segment = LineSegment2F::new(current_position, next_position);
+        trace_tiling!("... tile {:?}: fill {:?}, next step {:?}",
+                      tile_coords,
+                      clipped_line_segment,
+                      next_step_direction);
         object_builder.add_fill(scene_builder, clipped_line_segment, tile_coords);
 
         // Add extra fills if necessary.
@@ -277,9 +334,11 @@ fn process_line_segment(line_segment: LineSegment2F,
         // Adjust backdrop if necessary.
         if step.x() < 0 && last_step_direction == Some(StepDirection::X) {
             // Entered through right boundary.
+            trace_tiling!("... tile {:?}: backdrop +1", tile_coords);
             object_builder.adjust_alpha_tile_backdrop(tile_coords, 1);
         } else if step.x() > 0 && next_step_direction == Some(StepDirection::X) {
             // Leaving through right boundary.
+            trace_tiling!("... tile {:?}: backdrop -1", tile_coords);
             object_builder.adjust_alpha_tile_backdrop(tile_coords, -1);
         }
 
@@ -312,3 +371,484 @@ enum StepDirection {
     X,
     Y,
 }
+
+// These tests read the fills and tiles that the D3D9 build sends.
+#[cfg(all(test, feature="d3d9"))]
+mod test {
+    use crate::concurrent::executor::SequentialExecutor;
+    use crate::gpu_data::Fill;
+    use crate::options::BuildOptions;
+    use crate::paint::Paint;
+    use crate::scene::{DrawPath, Scene};
+    use crate::scene::test::build_d3d9;
+    use crate::tiles::{TILE_HEIGHT, TILE_WIDTH};
+    use crate::validate;
+    #[cfg(feature = "trace")]
+    use log::{LevelFilter, Log, Metadata, Record};
+    use pathfinder_color::ColorU;
+    use pathfinder_content::fill::FillRule;
+    use pathfinder_content::outline::{Contour, ContourIterFlags, MAX_POINTS_PER_CONTOUR, Outline};
+    use pathfinder_content::segment::{Segment, SegmentFlags};
+    use pathfinder_geometry::line_segment::LineSegment2F;
+    use pathfinder_geometry::rect::RectF;
+    use pathfinder_geometry::transform2d::Transform2F;
+    use pathfinder_geometry::vector::{Vector2F, vec2f};
+    use std::f32::consts::PI;
+
+    // Formats every message so that all the trace arguments actually get evaluated.
+    #[cfg(feature = "trace")]
+    struct FormattingLogger;
+
+    #[cfg(feature = "trace")]
+    impl Log for FormattingLogger {
+        fn enabled(&self, _: &Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &Record) {
+            drop(format!("{}", record.args()));
+        }
+
+        fn flush(&self) {}
+    }
+
+    #[cfg(feature = "trace")]
+    static LOGGER: FormattingLogger = FormattingLogger;
+
+    // Builds the scene and returns the fills and tiles it produced, formatted for comparison.
+    fn build_fills_and_tiles(scene: &mut Scene) -> (Vec<String>, Vec<String>) {
+        let output = build_d3d9(scene, BuildOptions::default(), &SequentialExecutor);
+        (output.fills().map(|fill| format!("{:?}", fill)).collect(),
+         output.tiles().map(|tile| format!("{:?}", tile)).collect())
+    }
+
+    fn build_fills_and_tiles_of_contour(contour: Contour) -> (Vec<String>, Vec<String>) {
+        let mut scene = Scene::new();
+        scene.set_view_box(RectF::new(vec2f(0.0, 0.0), vec2f(64.0, 64.0)));
+        let paint = scene.push_paint(&Paint::from_color(ColorU::black()));
+        let mut outline = Outline::new();
+        outline.push_contour(contour);
+        scene.push_draw_path(DrawPath::new(outline, paint));
+        build_fills_and_tiles(&mut scene)
+    }
+
+    #[cfg(feature = "trace")]
+    #[test]
+    fn test_tracing_does_not_alter_output() {
+        let mut contour = Contour::new();
+        contour.push_endpoint(vec2f(3.0, 5.0));
+        contour.push_cubic(vec2f(70.0, -10.0), vec2f(40.0, 80.0), vec2f(60.0, 50.0));
+        contour.push_endpoint(vec2f(8.0, 40.0));
+        contour.close();
+        let mut scene = Scene::new();
+        scene.set_view_box(RectF::new(vec2f(0.0, 0.0), vec2f(64.0, 64.0)));
+        let paint = scene.push_paint(&Paint::from_color(ColorU::black()));
+        let mut outline = Outline::new();
+        outline.push_contour(contour);
+        scene.push_draw_path(DrawPath::new(outline, paint));
+
+        log::set_max_level(LevelFilter::Off);
+        let untraced = build_fills_and_tiles(&mut scene);
+
+        let _ = log::set_logger(&LOGGER);
+        log::set_max_level(LevelFilter::Trace);
+        let traced = build_fills_and_tiles(&mut scene);
+        log::set_max_level(LevelFilter::Off);
+
+        assert!(!untraced.0.is_empty());
+        assert_eq!(untraced, traced);
+    }
+
+    #[test]
+    fn test_rect_fast_path_matches_general_path() {
+        let rects = [
+            RectF::new(vec2f(0.0, 0.0), vec2f(64.0, 64.0)),
+            RectF::new(vec2f(3.25, 5.5), vec2f(40.0, 27.75)),
+            RectF::new(vec2f(16.0, 16.0), vec2f(16.0, 32.0)),
+            RectF::new(vec2f(-4.5, 10.1), vec2f(30.3, 60.0)),
+            RectF::new(vec2f(20.0, 21.0), vec2f(3.0, 2.0)),
+        ];
+        for &rect in &rects {
+            // Clockwise and counterclockwise.
+            let corners =
+                [rect.origin(), rect.upper_right(), rect.lower_right(), rect.lower_left()];
+            for &reversed in &[false, true] {
+                let mut points = corners.to_vec();
+                if reversed {
+                    points.reverse();
+                }
+
+                let mut rect_contour = Contour::new();
+                points.iter().for_each(|&point| rect_contour.push_endpoint(point));
+                rect_contour.close();
+                let mut rect_outline = Outline::new();
+                rect_outline.push_contour(rect_contour.clone());
+                assert!(rect_outline.as_axis_aligned_rect().is_some());
+
+                // Repeating a corner adds a zero-length edge, which contributes nothing but
+                // defeats the rect check.
+                let mut general_contour = Contour::new();
+                general_contour.push_endpoint(points[0]);
+                points.iter().for_each(|&point| general_contour.push_endpoint(point));
+                general_contour.close();
+                let mut general_outline = Outline::new();
+                general_outline.push_contour(general_contour.clone());
+                assert!(general_outline.as_axis_aligned_rect().is_none());
+
+                let fast = build_fills_and_tiles_of_contour(rect_contour);
+                let general = build_fills_and_tiles_of_contour(general_contour);
+                assert!(!fast.1.is_empty());
+                assert_eq!(fast, general, "{:?} (reversed: {})", rect, reversed);
+            }
+        }
+    }
+
+    #[test]
+    fn test_split_overlong_contour_tiles_like_original() {
+        // A rect whose vertical edges are cut into more points than a contour can hold. Vertical
+        // edges add no fills, so the pieces `from_segments()` splits it into must produce exactly
+        // the fills and tiles of the plain rect.
+        let rect = RectF::new(vec2f(8.5, 8.5), vec2f(47.0, 47.0));
+        let steps = MAX_POINTS_PER_CONTOUR / 2 + 25_000;
+        let mut points = vec![rect.origin()];
+        points.extend((0..steps).map(|step| {
+            rect.upper_right() + vec2f(0.0, rect.height() * step as f32 / steps as f32)
+        }));
+        points.extend((0..steps).map(|step| {
+            rect.lower_left() - vec2f(0.0, rect.height() * step as f32 / steps as f32)
+        }));
+        let segments = (0..points.len()).map(|index| {
+            let next_index = (index + 1) % points.len();
+            let mut segment = Segment::line(LineSegment2F::new(points[index], points[next_index]));
+            if index == 0 {
+                segment.flags.insert(SegmentFlags::FIRST_IN_SUBPATH);
+            } else if next_index == 0 {
+                segment.flags.insert(SegmentFlags::CLOSES_SUBPATH);
+            }
+            segment
+        });
+        let outline = Outline::from_segments(segments);
+        assert_eq!(outline.len(), 2);
+
+        let build = |outline: Outline| {
+            let mut scene = Scene::new();
+            scene.set_view_box(RectF::new(vec2f(0.0, 0.0), vec2f(64.0, 64.0)));
+            let paint = scene.push_paint(&Paint::from_color(ColorU::black()));
+            scene.push_draw_path(DrawPath::new(outline, paint));
+            let options = BuildOptions::default();
+            assert_eq!(scene.build_and_validate(options.clone(), &SequentialExecutor), Ok(()));
+            build_fills_and_tiles(&mut scene)
+        };
+        assert_eq!(build(outline), build(Outline::from_rect(rect)));
+    }
+
+    const RASTER_SIZE: usize = 64;
+    const TILE_AREA: usize = TILE_WIDTH as usize * TILE_HEIGHT as usize;
+
+    // Builds a scene containing only the given outline and rasterizes the resulting fills and
+    // tiles on the CPU, the way the D3D9 shaders would. Returns coverage per pixel.
+    fn rasterize_with_tiler(outline: &Outline, fill_rule: FillRule) -> Vec<f32> {
+        let mut scene = Scene::new();
+        let size = RASTER_SIZE as f32;
+        scene.set_view_box(RectF::new(vec2f(0.0, 0.0), vec2f(size, size)));
+        let paint = scene.push_paint(&Paint::from_color(ColorU::black()));
+        let mut draw_path = DrawPath::new(outline.clone(), paint);
+        draw_path.set_fill_rule(fill_rule);
+        scene.push_draw_path(draw_path);
+
+        let output = build_d3d9(&mut scene, BuildOptions::default(), &SequentialExecutor);
+        let fills: Vec<_> = output.fills().collect();
+
+        let mut coverage = vec![0.0; RASTER_SIZE * RASTER_SIZE];
+        for tile in output.tiles() {
+            let mut tile_coverage = [tile.backdrop as f32; TILE_AREA];
+            if !tile.is_solid() {
+                for fill in fills.iter().filter(|fill| fill.link == tile.alpha_tile_id.0) {
+                    accumulate_fill(&mut tile_coverage, fill);
+                }
+            }
+
+            for (pixel_index, &pixel_coverage) in tile_coverage.iter().enumerate() {
+                let (tile_width, tile_height) = (TILE_WIDTH as usize, TILE_HEIGHT as usize);
+                let x = tile.tile_x as usize * tile_width + pixel_index % tile_width;
+                let y = tile.tile_y as usize * tile_height + pixel_index / tile_width;
+                if x >= RASTER_SIZE || y >= RASTER_SIZE {
+                    continue;
+                }
+                // This is what `sampleMask()` in `tile_fragment.inc.glsl` does.
+                coverage[y * RASTER_SIZE + x] = match fill_rule {
+                    FillRule::Winding => pixel_coverage.abs().min(1.0),
+                    FillRule::EvenOdd => 1.0 - (1.0 - pixel_coverage.rem_euclid(2.0)).abs(),
+                };
+            }
+        }
+        coverage
+    }
+
+    // Adds the signed area that a fill covers in each pixel of its tile, as the fill shader
+    // accumulates it.
+    fn accumulate_fill(tile_coverage: &mut [f32], fill: &Fill) {
+        let tile_width = TILE_WIDTH as usize;
+        for column in 0..tile_width {
+            let areas = validate::fill_row_areas_between(fill, column as f32, column as f32 + 1.0);
+            for (row, area) in areas.iter().enumerate() {
+                tile_coverage[row * tile_width + column] += area;
+            }
+        }
+    }
+
+    // An independent reference: a supersampling scanline rasterizer using the given fill rule on
+    // a finely flattened copy of the outline.
+    fn rasterize_with_scanlines(outline: &Outline, fill_rule: FillRule) -> Vec<f32> {
+        const SUBSAMPLES: usize = 16;
+
+        let flattened = outline.flattened(0.01);
+        let mut edges = vec![];
+        for contour in flattened.contours() {
+            for segment in contour.iter(ContourIterFlags::empty()) {
+                edges.push(segment.baseline);
+            }
+        }
+
+        let mut coverage = vec![0.0; RASTER_SIZE * RASTER_SIZE];
+        let sample_area = 1.0 / (SUBSAMPLES * SUBSAMPLES) as f32;
+        for sample_y in 0..(RASTER_SIZE * SUBSAMPLES) {
+            let y = (sample_y as f32 + 0.5) / SUBSAMPLES as f32;
+            let mut crossings: Vec<(f32, i32)> = edges.iter().filter(|edge| {
+                (edge.from_y() <= y) != (edge.to_y() <= y)
+            }).map(|edge| {
+                let t = (y - edge.from_y()) / (edge.to_y() - edge.from_y());
+                let winding = if edge.to_y() > edge.from_y() { 1 } else { -1 };
+                (edge.from_x() + (edge.to_x() - edge.from_x()) * t, winding)
+            }).collect();
+            crossings.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+
+            let (mut winding, mut next_crossing) = (0, 0);
+            for sample_x in 0..(RASTER_SIZE * SUBSAMPLES) {
+                let x = (sample_x as f32 + 0.5) / SUBSAMPLES as f32;
+                while next_crossing < crossings.len() && crossings[next_crossing].0 <= x {
+                    winding += crossings[next_crossing].1;
+                    next_crossing += 1;
+                }
+                let inside = match fill_rule {
+                    FillRule::Winding => winding != 0,
+                    FillRule::EvenOdd => winding % 2 != 0,
+                };
+                if inside {
+                    let pixel_index = sample_y / SUBSAMPLES * RASTER_SIZE + sample_x / SUBSAMPLES;
+                    coverage[pixel_index] += sample_area;
+                }
+            }
+        }
+        coverage
+    }
+
+    #[test]
+    fn test_extreme_winding_cancels_below_band() {
+        // The band of 256 circles enters each tile column it crosses with a backdrop delta of +256
+        // and leaves it with one of -256. Those deltas must cancel out exactly, so the bottom row
+        // of tiles, which lies below the band, is empty. The small rect extends the path's tiles
+        // down to that row.
+        let center = vec2f(32.5, 20.5);
+        let rect = RectF::new(vec2f(48.0, 52.0), vec2f(4.0, 4.0));
+        let mut outline = Outline::new();
+        for index in 0..256 {
+            outline.push_contour(circle(center, 12.0 + index as f32 * 0.025, false));
+        }
+        outline.push_contour(Contour::from_rect(rect));
+
+        let tiled = rasterize_with_tiler(&outline, FillRule::Winding);
+        for y in 48..RASTER_SIZE {
+            for x in 0..RASTER_SIZE {
+                let pixel_center = vec2f(x as f32 + 0.5, y as f32 + 0.5);
+                if !rect.contains_point(pixel_center) {
+                    let coverage = tiled[y * RASTER_SIZE + x];
+                    assert!(coverage < 0.01, "pixel ({}, {}) has coverage {}", x, y, coverage);
+                }
+            }
+        }
+    }
+
+    // Mirroring the unit circle horizontally reverses its orientation.
+    fn circle(center: Vector2F, radius: f32, reversed: bool) -> Contour {
+        let scale = vec2f(if reversed { -radius } else { radius }, radius);
+        let mut contour = Contour::new();
+        contour.push_ellipse(&(Transform2F::from_translation(center) *
+                               Transform2F::from_scale(scale)));
+        contour.close();
+        contour
+    }
+
+    fn polygon(points: &[Vector2F]) -> Contour {
+        let mut contour = Contour::new();
+        points.iter().for_each(|&point| contour.push_endpoint(point));
+        contour.close();
+        contour
+    }
+
+    #[test]
+    fn test_tiler_coverage_matches_scanline_reference() {
+        let center = vec2f(32.3, 31.7);
+        let star_points: Vec<_> = (0..5).map(|index| {
+            let angle = (index * 2) as f32 * PI * 2.0 / 5.0 - PI * 0.5;
+            center + vec2f(angle.cos(), angle.sin()) * 29.0
+        }).collect();
+
+        // Mirroring a shape reverses its contours, which must not turn fills into holes.
+        let triangle = polygon(&[vec2f(3.5, 60.2), vec2f(31.1, 2.75), vec2f(61.0, 49.9)]);
+        let reflection = Transform2F::from_translation(vec2f(64.0, 0.0)) *
+            Transform2F::from_scale(vec2f(-1.0, 1.0));
+
+        // Each shape comes with the largest per-pixel difference it tolerates. Curves get more
+        // slack because the tiler flattens them much more coarsely than the reference does, and
+        // so does the star, because area coverage only approximates the nonzero rule in pixels
+        // where edges cross.
+        let shapes = vec![
+            ("triangle", vec![triangle.clone()], 0.1),
+            ("reflected triangle", vec![triangle.transformed(&reflection)], 0.1),
+            ("circle", vec![circle(center, 27.5, false)], 0.25),
+            ("reflected circle", vec![circle(center, 27.5, true)], 0.25),
+            ("donut",
+             vec![circle(center, 27.5, false), circle(center, 12.25, true)],
+             0.3),
+            ("star", vec![polygon(&star_points)], 0.3),
+        ];
+
+        for (name, contours, max_difference) in shapes {
+            let mut outline = Outline::new();
+            contours.into_iter().for_each(|contour| outline.push_contour(contour));
+
+            let tiled = rasterize_with_tiler(&outline, FillRule::Winding);
+            let reference = rasterize_with_scanlines(&outline, FillRule::Winding);
+            let differences: Vec<f32> = tiled.iter().zip(reference.iter()).map(|(a, b)| {
+                (a - b).abs()
+            }).collect();
+            let worst = differences.iter().cloned().fold(0.0, f32::max);
+            let mean = differences.iter().sum::<f32>() / differences.len() as f32;
+            assert!(worst <= max_difference, "{}: worst pixel differs by {}", name, worst);
+            assert!(mean <= 0.01, "{}: mean difference {}", name, mean);
+
+            // Make sure the shape actually covers something, including its interior.
+            assert!(reference.iter().sum::<f32>() > 100.0, "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_fill_rules_match_scanline_reference() {
+        // The star's pentagonal center and the inside of the inner circle have a winding number
+        // of 2. The inner circle is big enough to contain solid tiles.
+        let center = vec2f(32.3, 31.7);
+        let star_points: Vec<_> = (0..5).map(|index| {
+            let angle = (index * 2) as f32 * PI * 2.0 / 5.0 - PI * 0.5;
+            center + vec2f(angle.cos(), angle.sin()) * 29.0
+        }).collect();
+        let shapes = vec![
+            ("star", vec![polygon(&star_points)]),
+            ("nested circles", vec![circle(center, 29.0, false), circle(center, 23.5, false)]),
+        ];
+
+        for (name, contours) in shapes {
+            let mut outline = Outline::new();
+            contours.into_iter().for_each(|contour| outline.push_contour(contour));
+
+            for &fill_rule in &[FillRule::Winding, FillRule::EvenOdd] {
+                let tiled = rasterize_with_tiler(&outline, fill_rule);
+                let reference = rasterize_with_scanlines(&outline, fill_rule);
+                let differences: Vec<f32> = tiled.iter().zip(reference.iter()).map(|(a, b)| {
+                    (a - b).abs()
+                }).collect();
+                // Where edges cross, area coverage only approximates either rule, and even-odd
+                // folds coverage past 1 back down, so allow more slack than for simple shapes.
+                let worst = differences.iter().cloned().fold(0.0, f32::max);
+                let mean = differences.iter().sum::<f32>() / differences.len() as f32;
+                assert!(worst <= 0.5, "{} ({:?}): worst difference {}", name, fill_rule, worst);
+                assert!(mean <= 0.01, "{} ({:?}): mean difference {}", name, fill_rule, mean);
+
+                let center_index = center.y() as usize * RASTER_SIZE + center.x() as usize;
+                let expected = if fill_rule == FillRule::Winding { 1.0 } else { 0.0 };
+                assert_eq!(tiled[center_index], expected, "{} ({:?})", name, fill_rule);
+            }
+        }
+    }
+
+    #[test]
+    fn test_vertical_edges_match_scanline_reference() {
+        // The vertical edges here start inside a tile, exactly on a tile column boundary, and at
+        // the bottom of the view box. The near-vertical ones lean by much less than a pixel.
+        let shapes = vec![
+            ("inside tile", polygon(&[vec2f(20.5, 3.0), vec2f(20.5, 58.0), vec2f(50.0, 30.0)])),
+            ("on boundary", polygon(&[vec2f(32.0, 60.0), vec2f(32.0, 4.0), vec2f(5.0, 40.0)])),
+            ("single tile", polygon(&[vec2f(40.0, 2.0), vec2f(40.0, 14.0), vec2f(47.5, 14.0)])),
+            ("near vertical",
+             polygon(&[vec2f(20.5, 3.0), vec2f(20.5001, 58.0), vec2f(50.0, 30.0)])),
+            ("near boundary",
+             polygon(&[vec2f(31.9999, 60.0), vec2f(32.0001, 4.0), vec2f(5.0, 40.0)])),
+        ];
+
+        for (name, contour) in shapes {
+            let mut outline = Outline::new();
+            outline.push_contour(contour);
+
+            let tiled = rasterize_with_tiler(&outline, FillRule::Winding);
+            let reference = rasterize_with_scanlines(&outline, FillRule::Winding);
+            let worst = tiled.iter().zip(reference.iter()).map(|(a, b)| {
+                (a - b).abs()
+            }).fold(0.0, f32::max);
+            assert!(worst <= 0.1, "{}: worst pixel differs by {}", name, worst);
+            assert!(reference.iter().sum::<f32>() > 40.0, "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_vertical_edge_in_one_tile_adds_no_fills() {
+        // The vertical edge's own fill is culled for having no width, so only the other two edges
+        // of the triangle produce fills: one each, all in the same tile.
+        let contour = polygon(&[vec2f(40.0, 2.0), vec2f(40.0, 14.0), vec2f(47.5, 14.0)]);
+        let (fills, tiles) = build_fills_and_tiles_of_contour(contour);
+        assert_eq!(fills.len(), 2, "{:?}", fills);
+        assert_eq!(tiles.len(), 1, "{:?}", tiles);
+    }
+
+    #[test]
+    fn test_extreme_winding_stays_solid() {
+        // A band of 256 nested circles wound the same way crosses the top of each tile column
+        // inside it, so those tiles accumulate backdrops that don't fit in an `i8`. Wrapping
+        // would make the winding number of the inner tiles zero. The small circle in the middle
+        // gives those tiles masks, so that their backdrops aren't just reduced to a sign.
+        let center = vec2f(32.5, 31.5);
+        let mut outline = Outline::new();
+        for index in 0..256 {
+            outline.push_contour(circle(center, 24.0 + index as f32 * 0.025, false));
+        }
+        outline.push_contour(circle(center, 4.0, false));
+
+        let tiled = rasterize_with_tiler(&outline, FillRule::Winding);
+        for y in 0..RASTER_SIZE {
+            for x in 0..RASTER_SIZE {
+                let pixel_center = vec2f(x as f32 + 0.5, y as f32 + 0.5);
+                if (pixel_center - center).length() < 22.0 {
+                    let coverage = tiled[y * RASTER_SIZE + x];
+                    assert!(coverage > 0.99, "pixel ({}, {}) has coverage {}", x, y, coverage);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_lower_flattening_tolerance_adds_fills() {
+        let fill_count = |flattening_tolerance| {
+            let mut scene = Scene::new();
+            scene.set_view_box(RectF::new(vec2f(0.0, 0.0), vec2f(64.0, 64.0)));
+            let paint = scene.push_paint(&Paint::from_color(ColorU::black()));
+            let mut outline = Outline::new();
+            outline.push_contour(circle(vec2f(32.5, 31.5), 28.0, false));
+            scene.push_draw_path(DrawPath::new(outline, paint));
+
+            let options = BuildOptions { flattening_tolerance, ..BuildOptions::default() };
+            build_d3d9(&mut scene, options, &SequentialExecutor).fills().count()
+        };
+
+        let (coarse, default, fine) = (fill_count(2.0), fill_count(0.25), fill_count(0.01));
+        assert!(coarse < default && default < fine, "{} {} {}", coarse, default, fine);
+    }
+}
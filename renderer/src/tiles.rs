@@ -61,6 +61,27 @@ impl TilingPathInfo {
     }
 }
 
+/// Narrows a winding number to fit in the `i8` backdrop of a tile.
+///
+/// Under the nonzero rule only whether the winding number is zero matters, so this saturates.
+/// Under the even-odd rule only its parity matters, so this wraps, which preserves parity.
+pub(crate) fn narrow_backdrop(backdrop: i32, fill_rule: FillRule) -> i8 {
+    match fill_rule {
+        FillRule::Winding => backdrop.max(i8::MIN as i32).min(i8::MAX as i32) as i8,
+        FillRule::EvenOdd => backdrop as i8,
+    }
+}
+
+/// Returns true if a tile without a mask is completely filled given its backdrop.
+///
+/// Under the even-odd rule an even backdrop leaves the tile empty.
+pub(crate) fn backdrop_fills_tile(backdrop: i8, fill_rule: FillRule) -> bool {
+    match fill_rule {
+        FillRule::Winding => backdrop != 0,
+        FillRule::EvenOdd => backdrop % 2 != 0,
+    }
+}
+
 pub fn round_rect_out_to_tile_bounds(rect: RectF) -> RectI {
     (rect * vec2f(1.0 / TILE_WIDTH as f32, 1.0 / TILE_HEIGHT as f32)).round_out().to_i32()
 }
@@ -68,4 +89,16 @@ pub fn round_rect_out_to_tile_bounds(rect: RectF) -> RectI {
 impl TileObjectPrimitive {
     #[inline]
     pub fn is_solid(&self) -> bool { !self.alpha_tile_id.is_valid() }
+
+    /// Returns true if this tile needs no mask and its backdrop fills it completely.
+    #[inline]
+    pub fn is_filled(&self) -> bool {
+        let fill_rule = if (self.ctrl as i32 >> TILE_CTRL_MASK_0_SHIFT) &
+                TILE_CTRL_MASK_EVEN_ODD != 0 {
+            FillRule::EvenOdd
+        } else {
+            FillRule::Winding
+        };
+        self.is_solid() && backdrop_fills_tile(self.backdrop, fill_rule)
+    }
 }
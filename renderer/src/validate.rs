@@ -0,0 +1,419 @@
+// pathfinder/renderer/src/validate.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Checks built render commands against the invariants the renderer relies on, and decodes them
+//! for analysis.
+//!
+//! This is meant for catching tiler bugs in tests and CI; it is too slow to run on every frame.
+
+use crate::gpu::d3d9::renderer::MAX_FILLS_PER_BATCH;
+use crate::gpu_data::{Fill, RenderCommand, TileObjectPrimitive};
+use crate::scene::PathId;
+use crate::tiles::{self, TILE_HEIGHT, TILE_WIDTH};
+use pathfinder_geometry::line_segment::LineSegment2F;
+use pathfinder_geometry::rect::RectF;
+use pathfinder_geometry::vector::{Vector2I, vec2f, vec2i};
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+
+/// An invariant that a list of render commands violates.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ValidationError {
+    /// A fill refers to a mask tile that no tile batch draws or clips.
+    FillReferencesUnknownMaskTile {
+        /// The alpha tile index that the fill links to.
+        link: u32,
+    },
+    /// A tile lies outside the tile rect of the view box.
+    TileOutsideViewBox {
+        /// The coordinates of the offending tile.
+        tile_coords: Vector2I,
+    },
+    /// A path has both a solid tile and a mask tile at the same coordinates.
+    SolidAndMaskTileOverlap {
+        /// The index of the path.
+        path_id: u32,
+        /// The coordinates of the offending tile.
+        tile_coords: Vector2I,
+    },
+    /// A single fill command holds more fills than the renderer can upload at once.
+    FillBatchTooLarge {
+        /// The number of fills in the command.
+        fill_count: usize,
+    },
+}
+
+/// Checks the D3D9 render commands of one built scene against these invariants:
+///
+/// * Every fill links to a mask tile that some tile batch draws or clips.
+///
+/// * Every tile lies within the tiles covering `view_box`.
+///
+/// * No path has a solid tile and a mask tile at the same coordinates.
+///
+/// * No fill command exceeds the renderer's fill batch size.
+///
+/// Returns the first violation found.
+pub fn validate_d3d9_commands(commands: &[RenderCommand], view_box: RectF)
+                              -> Result<(), ValidationError> {
+    let view_box_tile_rect = tiles::round_rect_out_to_tile_bounds(view_box);
+
+    let mut mask_tile_ids = HashSet::new();
+    let mut tile_solidity = HashMap::new();
+    for command in commands {
+        match *command {
+            RenderCommand::AddFillsD3D9(ref fills) if fills.len() > MAX_FILLS_PER_BATCH => {
+                return Err(ValidationError::FillBatchTooLarge { fill_count: fills.len() });
+            }
+            RenderCommand::DrawTilesD3D9(ref batch) => {
+                for tile in &batch.tiles {
+                    let tile_coords = vec2i(tile.tile_x as i32, tile.tile_y as i32);
+                    if !view_box_tile_rect.contains_point(tile_coords) {
+                        return Err(ValidationError::TileOutsideViewBox { tile_coords });
+                    }
+
+                    let is_solid = tile.is_solid();
+                    let path_id = tile.path_id.0;
+                    let key = (path_id, tile_coords.x(), tile_coords.y());
+                    if *tile_solidity.entry(key).or_insert(is_solid) != is_solid {
+                        return Err(ValidationError::SolidAndMaskTileOverlap {
+                            path_id,
+                            tile_coords,
+                        });
+                    }
+                    if !is_solid {
+                        mask_tile_ids.insert(tile.alpha_tile_id.0);
+                    }
+                }
+                for clip in &batch.clips {
+                    mask_tile_ids.insert(clip.dest_tile_id.0);
+                    mask_tile_ids.insert(clip.src_tile_id.0);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Fills are sent before the batches that draw their tiles, so check them afterward.
+    for command in commands {
+        if let RenderCommand::AddFillsD3D9(ref fills) = *command {
+            if let Some(fill) = fills.iter().find(|fill| !mask_tile_ids.contains(&fill.link)) {
+                return Err(ValidationError::FillReferencesUnknownMaskTile { link: fill.link });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// A fill decoded from its packed form, with the mask tile it draws into looked up.
+#[derive(Clone, Copy, Debug)]
+pub struct ResolvedFill {
+    /// The line segment in device pixels.
+    pub line_segment: LineSegment2F,
+    /// The coordinates of the mask tile that this fill contributes to.
+    pub tile_coords: Vector2I,
+    /// The path that the mask tile belongs to.
+    pub path_id: PathId,
+    /// The color index that the tile is shaded with.
+    pub color: u16,
+}
+
+/// Returns the fills in the D3D9 render commands of one built scene, decoded into device
+/// coordinates.
+///
+/// Fills belonging to clip path masks have no tile coordinates in the commands and are skipped.
+pub fn iter_resolved_fills<'a>(commands: &'a [RenderCommand])
+                               -> impl Iterator<Item = ResolvedFill> + 'a {
+    let mut mask_tiles: HashMap<u32, TileObjectPrimitive> = HashMap::new();
+    for command in commands {
+        if let RenderCommand::DrawTilesD3D9(ref batch) = *command {
+            for tile in batch.tiles.iter().filter(|tile| !tile.is_solid()) {
+                mask_tiles.insert(tile.alpha_tile_id.0, *tile);
+            }
+        }
+    }
+
+    let tile_size = vec2f(TILE_WIDTH as f32, TILE_HEIGHT as f32);
+    commands.iter().filter_map(|command| {
+        match *command {
+            RenderCommand::AddFillsD3D9(ref fills) => Some(fills),
+            _ => None,
+        }
+    }).flatten().filter_map(move |fill| {
+        let tile = mask_tiles.get(&fill.link)?;
+        let tile_coords = vec2i(tile.tile_x as i32, tile.tile_y as i32);
+        let tile_origin = tile_coords.to_f32() * tile_size;
+
+        let line_segment = tile_local_line_segment(fill);
+        Some(ResolvedFill {
+            line_segment: LineSegment2F::new(tile_origin + line_segment.from(),
+                                             tile_origin + line_segment.to()),
+            tile_coords,
+            path_id: tile.path_id,
+            color: tile.color,
+        })
+    })
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match *self {
+            ValidationError::FillReferencesUnknownMaskTile { link } => {
+                write!(formatter, "fill references unknown mask tile {}", link)
+            }
+            ValidationError::TileOutsideViewBox { tile_coords } => {
+                write!(formatter,
+                       "tile ({}, {}) lies outside the view box",
+                       tile_coords.x(),
+                       tile_coords.y())
+            }
+            ValidationError::SolidAndMaskTileOverlap { path_id, tile_coords } => {
+                write!(formatter,
+                       "path {} has both a solid and a mask tile at ({}, {})",
+                       path_id,
+                       tile_coords.x(),
+                       tile_coords.y())
+            }
+            ValidationError::FillBatchTooLarge { fill_count } => {
+                write!(formatter,
+                       "fill batch of {} exceeds the maximum of {}",
+                       fill_count,
+                       MAX_FILLS_PER_BATCH)
+            }
+        }
+    }
+}
+
+impl Error for ValidationError {}
+
+/// Returns the signed area, in square pixels, that a fill adds to each row of pixels in its mask
+/// tile, exactly as the D3D9 fill shader accumulates it.
+///
+/// A fill covers the part of its tile between its line and the bottom of the tile. The area is
+/// negative for fills running left to right and positive for fills running right to left, so the
+/// fills of a closed contour sum to its winding number times the covered area.
+pub fn fill_row_areas(fill: &Fill) -> [f32; TILE_HEIGHT as usize] {
+    fill_row_areas_between(fill, 0.0, TILE_WIDTH as f32)
+}
+
+/// Like `fill_row_areas()`, but only counts the part of each row between `min_x` and `max_x`,
+/// relative to the left of the tile. With a one pixel wide range, this gives the area the fill
+/// adds to each pixel of a column.
+pub fn fill_row_areas_between(fill: &Fill, min_x: f32, max_x: f32) -> [f32; TILE_HEIGHT as usize] {
+    let mut areas = [0.0; TILE_HEIGHT as usize];
+    let line_segment = tile_local_line_segment(fill);
+    let (from, to) = (line_segment.from(), line_segment.to());
+    if from.x() == to.x() {
+        return areas;
+    }
+
+    let sign = if to.x() > from.x() { -1.0 } else { 1.0 };
+    let (mut left, mut right) = if from.x() < to.x() { (from, to) } else { (to, from) };
+    if left.x() < min_x {
+        left = line_segment.sample(line_segment.solve_t_for_x(min_x));
+    }
+    if right.x() > max_x {
+        right = line_segment.sample(line_segment.solve_t_for_x(max_x));
+    }
+    let width = right.x() - left.x();
+    if width <= 0.0 {
+        return areas;
+    }
+    for (row, area) in areas.iter_mut().enumerate() {
+        // The covered height of the row at each x is `v = row + 1 - y`, clamped to [0, 1]. That's
+        // linear in x apart from the clamping, so integrate it in closed form.
+        let (left_v, right_v) = (row as f32 + 1.0 - left.y(), row as f32 + 1.0 - right.y());
+        let covered = if left_v == right_v {
+            left_v.clamp(0.0, 1.0) * width
+        } else {
+            (integrate_clamped(right_v) - integrate_clamped(left_v)) / (right_v - left_v) * width
+        };
+        *area = sign * covered;
+    }
+    areas
+}
+
+// The antiderivative of `v` clamped to [0, 1].
+fn integrate_clamped(v: f32) -> f32 {
+    if v <= 0.0 {
+        0.0
+    } else if v < 1.0 {
+        v * v * 0.5
+    } else {
+        v - 0.5
+    }
+}
+
+// Decodes the line segment of a fill, relative to the upper left of its tile.
+fn tile_local_line_segment(fill: &Fill) -> LineSegment2F {
+    // Fill endpoints are in 8.8 fixed point.
+    let segment = fill.line_segment;
+    let from = vec2f(segment.from_x as f32, segment.from_y as f32) * (1.0 / 256.0);
+    let to = vec2f(segment.to_x as f32, segment.to_y as f32) * (1.0 / 256.0);
+    LineSegment2F::new(from, to)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ValidationError, fill_row_areas, fill_row_areas_between, iter_resolved_fills};
+    use super::validate_d3d9_commands;
+    use crate::concurrent::executor::SequentialExecutor;
+    use crate::gpu_data::{Fill, RenderCommand};
+    use crate::options::BuildOptions;
+    use crate::paint::Paint;
+    use crate::scene::{DrawPath, Scene};
+    use crate::scene::test::build_d3d9;
+    use pathfinder_color::ColorU;
+    use pathfinder_content::outline::Outline;
+    use crate::tiles::{TILE_HEIGHT, TILE_WIDTH};
+    use pathfinder_geometry::line_segment::LineSegmentU16;
+    use pathfinder_geometry::rect::RectF;
+    use pathfinder_geometry::vector::vec2f;
+    use std::collections::HashMap;
+
+    const VIEW_BOX_SIZE: f32 = 64.0;
+
+    fn build_commands() -> Vec<RenderCommand> {
+        let mut scene = Scene::new();
+        scene.set_view_box(view_box());
+        let paint = scene.push_paint(&Paint::from_color(ColorU::black()));
+        let rect = RectF::new(vec2f(5.5, 7.25), vec2f(40.0, 30.0));
+        scene.push_draw_path(DrawPath::new(Outline::from_rect(rect), paint));
+        build_scene_commands(&mut scene)
+    }
+
+    fn build_scene_commands(scene: &mut Scene) -> Vec<RenderCommand> {
+        build_d3d9(scene, BuildOptions::default(), &SequentialExecutor).commands
+    }
+
+    fn view_box() -> RectF {
+        RectF::new(vec2f(0.0, 0.0), vec2f(VIEW_BOX_SIZE, VIEW_BOX_SIZE))
+    }
+
+    #[test]
+    fn test_built_scene_is_valid() {
+        assert_eq!(validate_d3d9_commands(&build_commands(), view_box()), Ok(()));
+    }
+
+    #[test]
+    fn test_corrupted_fill_link_fails_validation() {
+        let mut commands = build_commands();
+        for command in &mut commands {
+            if let RenderCommand::AddFillsD3D9(ref mut fills) = *command {
+                fills[0].link = 1000;
+            }
+        }
+
+        let error = validate_d3d9_commands(&commands, view_box()).unwrap_err();
+        assert_eq!(error, ValidationError::FillReferencesUnknownMaskTile { link: 1000 });
+        assert_eq!(error.to_string(), "fill references unknown mask tile 1000");
+    }
+
+    #[test]
+    fn test_tile_outside_view_box_fails_validation() {
+        let mut commands = build_commands();
+        for command in &mut commands {
+            if let RenderCommand::DrawTilesD3D9(ref mut batch) = *command {
+                batch.tiles[0].tile_x = 4;
+            }
+        }
+
+        let error = validate_d3d9_commands(&commands, view_box()).unwrap_err();
+        assert!(matches!(error, ValidationError::TileOutsideViewBox { tile_coords }
+                         if tile_coords.x() == 4));
+        assert!(error.to_string().contains("outside the view box"));
+    }
+
+    #[test]
+    fn test_resolved_fills_lie_in_their_tiles() {
+        let mut scene = Scene::new();
+        scene.set_view_box(view_box());
+        let red = scene.push_paint(&Paint::from_color(ColorU::new(255, 0, 0, 255)));
+        let blue = scene.push_paint(&Paint::from_color(ColorU::new(0, 0, 255, 255)));
+        let red_rect = RectF::new(vec2f(5.5, 7.25), vec2f(40.0, 30.0));
+        let blue_rect = RectF::new(vec2f(20.25, 40.5), vec2f(30.0, 20.0));
+        scene.push_draw_path(DrawPath::new(Outline::from_rect(red_rect), red));
+        scene.push_draw_path(DrawPath::new(Outline::from_rect(blue_rect), blue));
+        let commands = build_scene_commands(&mut scene);
+
+        let mut colors_by_path = HashMap::new();
+        let mut fill_count = 0;
+        for fill in iter_resolved_fills(&commands) {
+            let tile_origin = fill.tile_coords.to_f32() *
+                vec2f(TILE_WIDTH as f32, TILE_HEIGHT as f32);
+            let tile_rect = RectF::new(tile_origin, vec2f(TILE_WIDTH as f32, TILE_HEIGHT as f32));
+            assert!(tile_rect.contains_point(fill.line_segment.from()));
+            assert!(tile_rect.contains_point(fill.line_segment.to()));
+
+            // Each fill draws into a tile that its path's rect touches.
+            let path_rect = [red_rect, blue_rect][fill.path_id.0 as usize];
+            assert!(tile_rect.intersects(path_rect));
+
+            // Every fill of a path is shaded with the same color.
+            assert_eq!(*colors_by_path.entry(fill.path_id.0).or_insert(fill.color), fill.color);
+            fill_count += 1;
+        }
+
+        assert!(fill_count > 0);
+        assert_eq!(colors_by_path.len(), 2);
+        assert_ne!(colors_by_path[&0], colors_by_path[&1]);
+    }
+
+    // Makes a fill from tile-local pixel coordinates.
+    fn fill(from_x: f32, from_y: f32, to_x: f32, to_y: f32) -> Fill {
+        let to_fixed = |value: f32| (value * 256.0) as u16;
+        Fill {
+            line_segment: LineSegmentU16 {
+                from_x: to_fixed(from_x),
+                from_y: to_fixed(from_y),
+                to_x: to_fixed(to_x),
+                to_y: to_fixed(to_y),
+            },
+            link: 0,
+        }
+    }
+
+    #[test]
+    fn test_fill_row_areas() {
+        // Vertical fills cover nothing, wherever they are.
+        assert!(fill_row_areas(&fill(0.0, 0.0, 0.0, 16.0)).iter().all(|&area| area == 0.0));
+        assert!(fill_row_areas(&fill(8.0, 16.0, 8.0, 0.0)).iter().all(|&area| area == 0.0));
+
+        // A horizontal fill covers the rows below it, negatively when running left to right.
+        let areas = fill_row_areas(&fill(0.0, 8.0, 8.0, 8.0));
+        assert!(areas[..8].iter().all(|&area| area == 0.0));
+        assert!(areas[8..].iter().all(|&area| area == -8.0));
+        let areas = fill_row_areas(&fill(12.0, 4.5, 4.0, 4.5));
+        assert_eq!(areas[4], 4.0);
+        assert!(areas[5..].iter().all(|&area| area == 8.0));
+
+        // A diagonal across the tile covers a triangle in each row plus everything below.
+        let areas = fill_row_areas(&fill(16.0, 0.0, 0.0, 16.0));
+        for (row, &area) in areas.iter().enumerate() {
+            assert!((area - (row as f32 + 0.5)).abs() < 0.0001, "row {}: {}", row, area);
+        }
+
+        // In total, the fills cover the trapezoid between their line and the bottom of the tile.
+        let areas = fill_row_areas(&fill(3.0, 2.5, 13.5, 11.0));
+        let total: f32 = areas.iter().sum();
+        assert!((total + 10.5 * (16.0 - (2.5 + 11.0) * 0.5)).abs() < 0.001);
+
+        // Split into pixel columns, the areas add back up to those of the whole rows.
+        let diagonal = fill(3.0, 2.5, 13.5, 11.0);
+        for (row, &area) in areas.iter().enumerate() {
+            let column_sum: f32 = (0..16).map(|column| {
+                fill_row_areas_between(&diagonal, column as f32, column as f32 + 1.0)[row]
+            }).sum();
+            assert!((column_sum - area).abs() < 0.001, "row {}: {}", row, column_sum);
+        }
+    }
+}
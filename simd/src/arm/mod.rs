@@ -8,13 +8,13 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use std::arch::aarch64::{self, float32x2_t, float32x4_t, int32x2_t, int32x4_t};
-use std::arch::aarch64::{uint32x2_t, uint32x4_t};
-use std::f32;
-use std::fmt::{self, Debug, Formatter};
-use std::intrinsics::simd::*;
-use std::mem;
-use std::ops::{Add, BitAnd, BitOr, Div, Index, IndexMut, Mul, Not, Shr, Sub};
+use core::arch::aarch64::{self, float32x2_t, float32x4_t, int32x2_t, int32x4_t};
+use core::arch::aarch64::{uint32x2_t, uint32x4_t};
+use core::f32;
+use core::fmt::{self, Debug, Formatter};
+use core::intrinsics::simd::*;
+use core::mem;
+use core::ops::{Add, BitAnd, BitOr, Div, Index, IndexMut, Mul, Not, Shr, Sub};
 
 mod swizzle_f32x4;
 mod swizzle_i32x4;
@@ -9,7 +9,7 @@
 // except according to those terms.
 
 use crate::default::{F32x2, F32x4, I32x2, I32x4};
-use std::ops::{AddAssign, MulAssign, Neg, SubAssign};
+use core::ops::{AddAssign, MulAssign, Neg, SubAssign};
 
 // Two 32-bit floats
 
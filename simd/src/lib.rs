@@ -8,12 +8,19 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 #![cfg_attr(pf_rustc_nightly, allow(internal_features))]
 #![cfg_attr(pf_rustc_nightly, feature(link_llvm_intrinsics, core_intrinsics))]
 #![cfg_attr(pf_rustc_nightly, feature(simd_ffi))]
 #![cfg_attr(pf_rustc_nightly, feature(repr_simd))]
 
 //! A minimal SIMD abstraction, usable outside of Pathfinder.
+//!
+//! The `std` feature is on by default. Without it, this crate is `no_std` and needs the `libm`
+//! feature for the scalar floating-point functions that `core` lacks.
+
+#[cfg(not(any(feature = "std", feature = "libm")))]
+compile_error!("pathfinder_simd needs either the `std` or the `libm` feature");
 
 #[cfg(all(not(feature = "pf-no-simd"), pf_rustc_nightly, target_arch = "aarch64"))]
 pub use crate::arm as default;
@@ -35,9 +42,41 @@ pub use crate::x86 as default;
 #[cfg(all(pf_rustc_nightly, target_arch = "aarch64"))]
 pub mod arm;
 mod extras;
+mod math;
 pub mod scalar;
 #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
 pub mod x86;
 
 #[cfg(test)]
 mod test;
+
+/// Checks at runtime that the CPU supports the instructions that the `default` implementation
+/// uses.
+///
+/// The `x86` implementation uses SSE4.1 unconditionally, and on CPUs without it fails with an
+/// illegal instruction. Applications should call this at startup and report the error, perhaps
+/// suggesting a build with the `pf-no-simd` feature, which uses the `scalar` implementation.
+/// Other implementations always pass.
+#[cfg(feature = "std")]
+pub fn check_cpu_features() -> Result<(), MissingCpuFeature> {
+    #[cfg(all(not(feature = "pf-no-simd"), any(target_arch = "x86", target_arch = "x86_64")))]
+    {
+        if !is_x86_feature_detected!("sse4.1") {
+            return Err(MissingCpuFeature("SSE4.1"));
+        }
+    }
+    Ok(())
+}
+
+/// The error returned by `check_cpu_features()`, naming the instruction set the CPU lacks.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MissingCpuFeature(pub &'static str);
+
+impl core::fmt::Display for MissingCpuFeature {
+    fn fmt(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(formatter, "CPU lacks {}", self.0)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MissingCpuFeature {}
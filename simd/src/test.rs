@@ -689,3 +689,17 @@ fn test_f32x4s_basic_ops() {
     assert_eq!(c.ceil(), F32x4S::new(-1.0, 2.0, -20.0, 4.0));
     assert_eq!(c.to_i32x4().to_f32x4(), F32x4S::new(-1.0, 1.0, -20.0, 4.0));
 }
+
+// CPU features
+
+#[cfg(feature = "std")]
+#[test]
+fn test_check_cpu_features() {
+    let result = crate::check_cpu_features();
+    #[cfg(all(not(feature = "pf-no-simd"), any(target_arch = "x86", target_arch = "x86_64")))]
+    assert_eq!(result.is_ok(), is_x86_feature_detected!("sse4.1"));
+    #[cfg(not(all(not(feature = "pf-no-simd"), any(target_arch = "x86", target_arch = "x86_64"))))]
+    assert!(result.is_ok());
+
+    assert_eq!(crate::MissingCpuFeature("SSE4.1").to_string(), "CPU lacks SSE4.1");
+}
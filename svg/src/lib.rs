@@ -35,13 +35,43 @@ use usvg::{LineJoin as UsvgLineJoin, Node, NodeExt, NodeKind, Opacity, Paint as
 use usvg::{PathSegment as UsvgPathSegment, Rect as UsvgRect, SpreadMethod, Stop};
 use usvg::{Transform as UsvgTransform, Tree, Visibility};
 
-const HAIRLINE_STROKE_WIDTH: f32 = 0.0333;
+/// The default value of `SVGBuildOptions::min_stroke_width`.
+pub const DEFAULT_MIN_STROKE_WIDTH: f32 = 0.0333;
 
 pub struct SVGScene {
     pub scene: Scene,
     pub result_flags: BuildResultFlags,
+    /// Every unsupported feature that was dropped, one entry per node that used it.
+    pub warnings: Vec<UnsupportedFeature>,
     pub clip_paths: HashMap<String, Outline>,
+    clip_path_fill_rules: HashMap<String, FillRule>,
     gradients: HashMap<String, GradientInfo>,
+    min_stroke_width: f32,
+    view_box_clip: Option<RectF>,
+}
+
+/// Options that control how an SVG tree is built into a scene.
+#[derive(Clone, Copy, Debug)]
+pub struct SVGBuildOptions {
+    /// A transform applied to all of the content.
+    pub transform: Transform2F,
+    /// Strokes thinner than this, in the units of the stroked path, are widened to it so that
+    /// hairlines stay visible. Lower it for high-DPI output and raise it for low-DPI output.
+    pub min_stroke_width: f32,
+    /// Keeps content that lies outside the view box, as `overflow: visible` on the root `<svg>`
+    /// element would. By default such content is clipped away, matching `overflow: hidden`.
+    pub overflow_visible: bool,
+}
+
+impl Default for SVGBuildOptions {
+    #[inline]
+    fn default() -> SVGBuildOptions {
+        SVGBuildOptions {
+            transform: Transform2F::default(),
+            min_stroke_width: DEFAULT_MIN_STROKE_WIDTH,
+            overflow_visible: false,
+        }
+    }
 }
 
 bitflags! {
@@ -59,30 +89,79 @@ bitflags! {
     }
 }
 
+/// An unsupported feature that a node used, which was dropped while building the scene.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UnsupportedFeature {
+    /// The ID of the node, or an empty string if it has none.
+    pub node_id: String,
+    /// The feature, as one or more of the `BuildResultFlags`.
+    pub kind: BuildResultFlags,
+}
+
+impl Display for UnsupportedFeature {
+    fn fmt(&self, formatter: &mut Formatter) -> FormatResult {
+        if self.node_id.is_empty() {
+            write!(formatter, "{} (unnamed node)", self.kind)
+        } else {
+            write!(formatter, "{} (node \"{}\")", self.kind, self.node_id)
+        }
+    }
+}
+
 impl SVGScene {
-    // TODO(pcwalton): Allow a global transform to be set.
+    // NB: `usvg` converts `<text>` elements to paths itself, so there is no text node to handle
+    // here. Text that it can't shape (e.g. because no fonts were found) is dropped before we ever
+    // see the tree.
     #[inline]
     pub fn from_tree(tree: &Tree) -> SVGScene {
         SVGScene::from_tree_and_scene(tree, Scene::new())
     }
 
-    // TODO(pcwalton): Allow a global transform to be set.
+    #[inline]
     pub fn from_tree_and_scene(tree: &Tree, scene: Scene) -> SVGScene {
+        SVGScene::from_tree_and_scene_with_transform(tree, scene, Transform2F::default())
+    }
+
+    /// Builds the tree into the given scene with `transform` applied to all of its content.
+    ///
+    /// The view box of the scene is set to the bounding box of the transformed SVG view box.
+    #[inline]
+    pub fn from_tree_and_scene_with_transform(tree: &Tree, scene: Scene, transform: Transform2F)
+                                              -> SVGScene {
+        let options = SVGBuildOptions { transform, ..SVGBuildOptions::default() };
+        SVGScene::from_tree_and_scene_with_options(tree, scene, &options)
+    }
+
+    /// Builds the tree into the given scene according to `options`.
+    ///
+    /// The view box of the scene is set to the bounding box of the transformed SVG view box.
+    /// Content outside it is clipped away unless `options.overflow_visible` is set.
+    pub fn from_tree_and_scene_with_options(tree: &Tree, scene: Scene, options: &SVGBuildOptions)
+                                            -> SVGScene {
         // TODO(pcwalton): Maybe have a `SVGBuilder` type to hold the clip path IDs and other
         // transient data separate from `SVGScene`?
         let mut built_svg = SVGScene {
             scene,
             result_flags: BuildResultFlags::empty(),
+            warnings: vec![],
             clip_paths: HashMap::new(),
+            clip_path_fill_rules: HashMap::new(),
             gradients: HashMap::new(),
+            min_stroke_width: options.min_stroke_width,
+            view_box_clip: None,
         };
 
         let root = &tree.root();
         match *root.borrow() {
             NodeKind::Svg(ref svg) => {
-                built_svg.scene.set_view_box(usvg_rect_to_euclid_rect(&svg.view_box.rect));
+                let view_box = options.transform * usvg_rect_to_euclid_rect(&svg.view_box.rect);
+                built_svg.scene.set_view_box(view_box);
+                if !options.overflow_visible {
+                    built_svg.view_box_clip = Some(view_box);
+                }
+                let state = State { transform: options.transform, ..State::new() };
                 for kid in root.children() {
-                    built_svg.process_node(&kid, &State::new(), &mut None);
+                    built_svg.process_node(&kid, &state, &mut None);
                 }
             }
             _ => unreachable!(),
@@ -91,25 +170,41 @@ impl SVGScene {
         built_svg
     }
 
+    /// Returns a transform that mirrors the tree vertically within its view box.
+    ///
+    /// SVG coordinates are y-down. Building with this transform yields y-up output for consumers
+    /// that expect OpenGL-style coordinates, while keeping the same view box.
+    pub fn flip_y_transform(tree: &Tree) -> Transform2F {
+        let view_box = usvg_rect_to_euclid_rect(&tree.svg_node().view_box.rect);
+        Transform2F::from_translation(vec2f(0.0, view_box.min_y() + view_box.max_y())) *
+            Transform2F::from_scale(vec2f(1.0, -1.0))
+    }
+
     fn process_node(&mut self,
                     node: &Node,
                     state: &State,
-                    clip_outline: &mut Option<Outline>) {
+                    clip_outline: &mut Option<(Outline, FillRule)>) {
         let mut state = (*state).clone();
         let node_transform = usvg_transform_to_transform_2d(&node.transform());
         state.transform = state.transform * node_transform;
         match *node.borrow() {
             NodeKind::Group(ref group) => {
+                // Group opacity is applied to each path in the group rather than to the group as
+                // a whole, so it differs from the spec where children overlap.
+                state.opacity *= group.opacity.value() as f32;
                 if group.filter.is_some() {
-                    self.result_flags.insert(BuildResultFlags::UNSUPPORTED_FILTER_ATTR);
+                    self.warn_unsupported(node, BuildResultFlags::UNSUPPORTED_FILTER_ATTR);
                 }
                 if group.mask.is_some() {
-                    self.result_flags.insert(BuildResultFlags::UNSUPPORTED_MASK_ATTR);
+                    self.warn_unsupported(node, BuildResultFlags::UNSUPPORTED_MASK_ATTR);
                 }
                 if let Some(ref clip_path_name) = group.clip_path {
                     if let Some(clip_outline) = self.clip_paths.get(clip_path_name) {
                         let transformed_outline = clip_outline.clone().transformed(&state.transform);
                         let mut clip_path = ClipPath::new(transformed_outline);
+                        if let Some(&fill_rule) = self.clip_path_fill_rules.get(clip_path_name) {
+                            clip_path.set_fill_rule(fill_rule);
+                        }
                         clip_path.set_clip_path(state.clip_path);
                         clip_path.set_name(format!("ClipPath({})", clip_path_name));
                         let clip_path_id = self.scene.push_clip_path(clip_path);
@@ -123,12 +218,19 @@ impl SVGScene {
             }
             NodeKind::Path(ref path) if state.path_destination == PathDestination::Clip => {
                 // TODO(pcwalton): Multiple clip paths.
+                // `usvg` resolves the `clip-rule` property of clip path children into the fill
+                // rule of the path, so that's what determines the interior of the clip.
+                let fill_rule = match path.fill {
+                    Some(ref fill) => FillRule::from_usvg_fill_rule(fill.rule),
+                    None => FillRule::Winding,
+                };
                 let path = UsvgPathToSegments::new(path.data.iter().cloned());
                 let path = Transform2FPathIter::new(path, &state.transform);
                 if clip_outline.is_some() {
-                    self.result_flags.insert(BuildResultFlags::UNSUPPORTED_MULTIPLE_CLIP_PATHS);
+                    self.warn_unsupported(node,
+                                          BuildResultFlags::UNSUPPORTED_MULTIPLE_CLIP_PATHS);
                 }
-                *clip_outline = Some(Outline::from_segments(path));
+                *clip_outline = Some((Outline::from_segments(path), fill_rule));
             }
             NodeKind::Path(ref path) if state.path_destination == PathDestination::Draw &&
                     path.visibility == Visibility::Visible => {
@@ -138,6 +240,7 @@ impl SVGScene {
 
                     let name = format!("Fill({})", node.id());
                     self.push_draw_path(outline,
+                                        node,
                                         name,
                                         &state,
                                         &fill.paint,
@@ -147,7 +250,7 @@ impl SVGScene {
 
                 if let Some(ref stroke) = path.stroke {
                     let stroke_style = StrokeStyle {
-                        line_width: f32::max(stroke.width.value() as f32, HAIRLINE_STROKE_WIDTH),
+                        line_width: f32::max(stroke.width.value() as f32, self.min_stroke_width),
                         line_cap: LineCap::from_usvg_line_cap(stroke.linecap),
                         line_join: LineJoin::from_usvg_line_join(stroke.linejoin,
                                                                  stroke.miterlimit.value() as f32),
@@ -169,6 +272,7 @@ impl SVGScene {
 
                     let name = format!("Stroke({})", node.id());
                     self.push_draw_path(outline,
+                                        node,
                                         name,
                                         &state,
                                         &stroke.paint,
@@ -184,7 +288,9 @@ impl SVGScene {
                     self.process_node(&kid, &state, &mut clip_outline);
                 }
 
-                self.clip_paths.insert(node.id().to_owned(), clip_outline.unwrap());
+                let (clip_outline, fill_rule) = clip_outline.unwrap();
+                self.clip_paths.insert(node.id().to_owned(), clip_outline);
+                self.clip_path_fill_rules.insert(node.id().to_owned(), fill_rule);
             }
             NodeKind::Defs => {
                 // FIXME(pcwalton): This is wrong.
@@ -205,28 +311,45 @@ impl SVGScene {
                 let from = vec2f(svg_radial_gradient.fx as f32, svg_radial_gradient.fy as f32);
                 let to   = vec2f(svg_radial_gradient.cx as f32, svg_radial_gradient.cy as f32);
                 let radii = F32x2::new(0.0, svg_radial_gradient.r.value() as f32);
-                let gradient = Gradient::radial(LineSegment2F::new(from, to), radii);
-                self.add_gradient(gradient,
-                                  svg_radial_gradient.id.clone(),
-                                  &svg_radial_gradient.base)
+                let mut gradient = Gradient::radial(LineSegment2F::new(from, to), radii);
+                if radii.y() > 0.0 {
+                    self.add_gradient(gradient,
+                                      svg_radial_gradient.id.clone(),
+                                      &svg_radial_gradient.base)
+                } else {
+                    // SVG paints the whole area with the last stop of a zero-radius gradient.
+                    if let Some(stop) = svg_radial_gradient.base.stops.last() {
+                        gradient.add(ColorStop::from_usvg_stop(stop));
+                    }
+                    let transform = Transform2F::default();
+                    self.gradients.insert(svg_radial_gradient.id.clone(),
+                                          GradientInfo { gradient, transform });
+                }
             }
             NodeKind::Filter(..) => {
-                self.result_flags
-                    .insert(BuildResultFlags::UNSUPPORTED_FILTER_NODE);
+                self.warn_unsupported(node, BuildResultFlags::UNSUPPORTED_FILTER_NODE);
             }
             NodeKind::Image(..) => {
-                self.result_flags
-                    .insert(BuildResultFlags::UNSUPPORTED_IMAGE_NODE);
+                self.warn_unsupported(node, BuildResultFlags::UNSUPPORTED_IMAGE_NODE);
             }
             NodeKind::Mask(..) => {
-                self.result_flags
-                    .insert(BuildResultFlags::UNSUPPORTED_MASK_NODE);
+                self.warn_unsupported(node, BuildResultFlags::UNSUPPORTED_MASK_NODE);
             }
             NodeKind::Pattern(..) => {
-                self.result_flags
-                    .insert(BuildResultFlags::UNSUPPORTED_PATTERN_NODE);
+                self.warn_unsupported(node, BuildResultFlags::UNSUPPORTED_PATTERN_NODE);
+            }
+            NodeKind::Svg(ref svg) => {
+                // usvg normally resolves nested `<svg>` elements into groups, but if one survives,
+                // map its view box onto its viewport before drawing its children.
+                let view_box_transform = usvg::utils::view_box_to_transform(svg.view_box.rect,
+                                                                            svg.view_box.aspect,
+                                                                            svg.size);
+                state.transform = state.transform *
+                    usvg_transform_to_transform_2d(&view_box_transform);
+                for kid in node.children() {
+                    self.process_node(&kid, &state, clip_outline)
+                }
             }
-            NodeKind::Svg(..) => unreachable!(),
         }
     }
 
@@ -266,17 +389,41 @@ impl SVGScene {
 
     fn push_draw_path(&mut self,
                       mut outline: Outline,
+                      node: &Node,
                       name: String,
                       state: &State,
                       paint: &UsvgPaint,
                       opacity: Opacity,
                       fill_rule: UsvgFillRule) {
-        outline.transform(&state.transform);
+        let opacity = opacity.value() as f32 * state.opacity;
+        let mut paint_result_flags = BuildResultFlags::empty();
         let paint = Paint::from_svg_paint(paint,
                                           &state.transform,
                                           opacity,
                                           &self.gradients,
-                                          &mut self.result_flags);
+                                          &mut paint_result_flags);
+        if !paint_result_flags.is_empty() {
+            self.warn_unsupported(node, paint_result_flags);
+        }
+
+        // Nothing would be drawn, so don't make the renderer tile the path.
+        if opacity == 0.0 || paint.is_fully_transparent() {
+            return;
+        }
+
+        outline.transform(&state.transform);
+        // The renderer only clips to its own view box, padded by a tile, and the demo replaces the
+        // scene's view box with the window, so the SVG's view box has to be clipped here. Paths
+        // inside it are left alone.
+        let view_box_clip = self.view_box_clip.filter(|clip| !clip.contains_rect(outline.bounds()));
+        if let Some(view_box_clip) = view_box_clip {
+            outline.close_all_contours();
+            outline.clip_against_rect(view_box_clip);
+            if outline.is_empty() {
+                return;
+            }
+        }
+
         let style = self.scene.push_paint(&paint);
         let fill_rule = FillRule::from_usvg_fill_rule(fill_rule);
         let mut path = DrawPath::new(outline, style);
@@ -285,6 +432,11 @@ impl SVGScene {
         path.set_name(name);
         self.scene.push_draw_path(path);
     }
+
+    fn warn_unsupported(&mut self, node: &Node, kind: BuildResultFlags) {
+        self.result_flags.insert(kind);
+        self.warnings.push(UnsupportedFeature { node_id: node.id().to_owned(), kind });
+    }
 }
 
 impl Display for BuildResultFlags {
@@ -325,7 +477,7 @@ impl Display for BuildResultFlags {
 trait PaintExt {
     fn from_svg_paint(svg_paint: &UsvgPaint,
                       transform: &Transform2F,
-                      opacity: Opacity,
+                      opacity: f32,
                       gradients: &HashMap<String, GradientInfo>,
                       result_flags: &mut BuildResultFlags)
                       -> Self;
@@ -335,7 +487,7 @@ impl PaintExt for Paint {
     #[inline]
     fn from_svg_paint(svg_paint: &UsvgPaint,
                       transform: &Transform2F,
-                      opacity: Opacity,
+                      opacity: f32,
                       gradients: &HashMap<String, GradientInfo>,
                       result_flags: &mut BuildResultFlags)
                       -> Paint {
@@ -345,8 +497,14 @@ impl PaintExt for Paint {
             UsvgPaint::Link(ref id) => {
                 match gradients.get(id) {
                     Some(ref gradient_info) => {
-                        paint = Paint::from_gradient(gradient_info.gradient.clone());
-                        paint.apply_transform(&(*transform * gradient_info.transform));
+                        match *gradient_info.gradient.stops() {
+                            // A gradient with one stop is a solid color.
+                            [stop] => paint = Paint::from_color(stop.color),
+                            _ => {
+                                paint = Paint::from_gradient(gradient_info.gradient.clone());
+                                paint.apply_transform(&(*transform * gradient_info.transform));
+                            }
+                        }
                     }
                     None => {
                         // TODO(pcwalton)
@@ -358,7 +516,7 @@ impl PaintExt for Paint {
         }
 
         let mut base_color = paint.base_color().to_f32();
-        base_color.set_a(base_color.a() * opacity.value() as f32);
+        base_color.set_a(base_color.a() * opacity);
         paint.set_base_color(base_color.to_u8());
 
         paint
@@ -520,7 +678,7 @@ trait ColorStopExt {
 impl ColorStopExt for ColorStop {
     fn from_usvg_stop(usvg_stop: &Stop) -> ColorStop {
         let mut color = ColorU::from_svg_color(usvg_stop.color);
-        color.a = (usvg_stop.opacity.value() * 255.0) as u8;
+        color.a = (usvg_stop.opacity.value() * 255.0).round() as u8;
         ColorStop::new(color, usvg_stop.offset.value() as f32)
     }
 }
@@ -533,6 +691,8 @@ struct State {
     transform: Transform2F,
     // The current clip path in effect.
     clip_path: Option<ClipPathId>,
+    // The product of the opacities of the enclosing groups.
+    opacity: f32,
 }
 
 impl State {
@@ -541,6 +701,7 @@ impl State {
             path_destination: PathDestination::Draw,
             transform: Transform2F::default(),
             clip_path: None,
+            opacity: 1.0,
         }
     }
 }
@@ -556,3 +717,277 @@ struct GradientInfo {
     gradient: Gradient,
     transform: Transform2F,
 }
+
+#[cfg(test)]
+mod test {
+    use super::{BuildResultFlags, SVGBuildOptions, SVGScene, UnsupportedFeature};
+    use pathfinder_color::ColorU;
+    use pathfinder_content::gradient::GradientGeometry;
+    use pathfinder_export::{Export, FileFormat};
+    use pathfinder_geometry::rect::RectF;
+    use pathfinder_geometry::vector::vec2f;
+    use pathfinder_renderer::concurrent::executor::SequentialExecutor;
+    use pathfinder_renderer::options::BuildOptions;
+    use pathfinder_renderer::scene::{DrawPathId, Scene};
+    use usvg::{Options, Tree};
+
+    const TEST_SVG: &str = r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 64 64">
+        <rect x="-8" y="-8" width="80" height="48" fill="black"/>
+    </svg>"#;
+
+    // Returns the upper left corners of the solid tiles of the built scene.
+    fn solid_tile_origins(mut svg: SVGScene) -> Vec<(i32, i32)> {
+        let vertices = svg.scene.build_solid_tiles(BuildOptions::default(), &SequentialExecutor)
+                                .triangles();
+        let mut origins: Vec<_> = vertices.chunks(6).map(|triangles| {
+            let origin = triangles[0].position;
+            (origin.x() as i32, origin.y() as i32)
+        }).collect();
+        origins.sort();
+        origins
+    }
+
+    #[test]
+    fn test_flip_y_mirrors_tiles() {
+        let tree = Tree::from_data(TEST_SVG.as_bytes(), &Options::default()).unwrap();
+        let flip_y = SVGScene::flip_y_transform(&tree);
+        assert_eq!(flip_y * vec2f(8.0, 0.0), vec2f(8.0, 64.0));
+
+        let unflipped = SVGScene::from_tree(&tree);
+        let flipped = SVGScene::from_tree_and_scene_with_transform(&tree, Scene::new(), flip_y);
+        assert_eq!(flipped.scene.view_box(), unflipped.scene.view_box());
+
+        let unflipped_origins = solid_tile_origins(unflipped);
+        assert!(!unflipped_origins.is_empty());
+        let mut mirrored_origins: Vec<_> = unflipped_origins.iter().map(|&(x, y)| {
+            // Mirroring maps the lower left corner of a tile to the upper left corner.
+            let mirrored = flip_y * vec2f(x as f32, y as f32 + 16.0);
+            (mirrored.x() as i32, mirrored.y() as i32)
+        }).collect();
+        mirrored_origins.sort();
+        assert_eq!(solid_tile_origins(flipped), mirrored_origins);
+    }
+
+    #[test]
+    fn test_nested_svg_maps_view_box_to_viewport() {
+        const NESTED_SVG: &str = r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 64 64">
+            <svg x="32" y="8" width="32" height="32" viewBox="0 0 16 16">
+                <rect width="16" height="16" fill="black"/>
+            </svg>
+        </svg>"#;
+        // The rect lands on (32, 8) to (64, 40), which covers only the middle row of its tiles.
+        let tree = Tree::from_data(NESTED_SVG.as_bytes(), &Options::default()).unwrap();
+        assert_eq!(solid_tile_origins(SVGScene::from_tree(&tree)), vec![(32, 16), (48, 16)]);
+    }
+
+    #[test]
+    fn test_exported_svg_round_trips() {
+        const SHAPES_SVG: &str = r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 64 48">
+            <rect x="4" y="6" width="20" height="10" fill="red"/>
+            <g transform="translate(8 4) scale(1.5)">
+                <circle cx="20" cy="16" r="6" fill="lime" stroke="blue" stroke-width="2"/>
+            </g>
+            <path d="M 40 40 Q 60 10 50 44 C 44 46 42 44 40 40 z" fill="black"/>
+        </svg>"#;
+
+        // Returns the view box and, for each path, its bounds in thousandths of a pixel and its
+        // color.
+        fn summarize(scene: &Scene) -> (RectF, Vec<([i32; 4], ColorU)>) {
+            let paths = (0..scene.draw_path_count()).map(|index| {
+                let draw_path = scene.get_draw_path(DrawPathId(index));
+                let bounds = draw_path.outline().bounds();
+                let bounds = [bounds.min_x(), bounds.min_y(), bounds.max_x(), bounds.max_y()];
+                let color = scene.get_paint(draw_path.paint()).base_color();
+                (bounds.map(|value| (value * 1000.0).round() as i32), color)
+            }).collect();
+            (scene.view_box(), paths)
+        }
+
+        let tree = Tree::from_data(SHAPES_SVG.as_bytes(), &Options::default()).unwrap();
+        let scene = SVGScene::from_tree(&tree).scene;
+        assert_eq!(scene.draw_path_count(), 4);
+
+        let mut exported = vec![];
+        scene.export(&mut exported, FileFormat::SVG).unwrap();
+        let tree = Tree::from_data(&exported, &Options::default()).unwrap();
+        let reparsed = SVGScene::from_tree(&tree).scene;
+        assert_eq!(summarize(&reparsed), summarize(&scene));
+    }
+
+    #[test]
+    fn test_fill_and_stroke_share_gradient_paint() {
+        const GRADIENT_SVG: &str = r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 64 64">
+            <linearGradient id="g" x1="0" y1="0" x2="1" y2="0">
+                <stop offset="0.1" stop-color="red"/>
+                <stop offset="0.7" stop-color="blue"/>
+            </linearGradient>
+            <rect x="8" y="8" width="48" height="48" fill="url(#g)" stroke="url(#g)"/>
+        </svg>"#;
+        let tree = Tree::from_data(GRADIENT_SVG.as_bytes(), &Options::default()).unwrap();
+        let scene = SVGScene::from_tree(&tree).scene;
+        assert_eq!(scene.draw_path_count(), 2);
+        assert_eq!(scene.get_draw_path(DrawPathId(0)).paint(),
+                   scene.get_draw_path(DrawPathId(1)).paint());
+    }
+
+    #[test]
+    fn test_fill_and_group_opacity_become_paint_alpha() {
+        const OPACITY_SVG: &str = r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 64 64">
+            <rect x="8" y="8" width="16" height="16" fill="red" fill-opacity="0.5"/>
+            <g opacity="0.5">
+                <rect x="32" y="8" width="16" height="16" fill="red" fill-opacity="0.5"/>
+            </g>
+        </svg>"#;
+        let tree = Tree::from_data(OPACITY_SVG.as_bytes(), &Options::default()).unwrap();
+        let scene = SVGScene::from_tree(&tree).scene;
+        assert_eq!(scene.draw_path_count(), 2);
+        let alpha_of = |index| {
+            scene.get_paint(scene.get_draw_path(DrawPathId(index)).paint()).base_color().a
+        };
+        assert_eq!(alpha_of(0), 128);
+        assert_eq!(alpha_of(1), 64);
+    }
+
+    #[test]
+    fn test_fully_transparent_paths_are_skipped() {
+        const TRANSPARENT_SVG: &str =
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 64 64">
+            <rect x="8" y="8" width="48" height="48" fill="red" fill-opacity="0"/>
+            <rect x="8" y="8" width="48" height="48" fill="red" stroke="blue"
+                  stroke-opacity="0"/>
+        </svg>"#;
+        let tree = Tree::from_data(TRANSPARENT_SVG.as_bytes(), &Options::default()).unwrap();
+        let scene = SVGScene::from_tree(&tree).scene;
+        assert_eq!(scene.draw_path_count(), 1);
+        assert_eq!(scene.get_draw_path(DrawPathId(0)).name(), "Fill()");
+    }
+
+    #[test]
+    fn test_radial_gradients() {
+        const RADIAL_SVG: &str = r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 64 64">
+            <radialGradient id="g" cx="0.5" cy="0.5" r="0.5">
+                <stop offset="0" stop-color="red"/>
+                <stop offset="1" stop-color="blue"/>
+            </radialGradient>
+            <radialGradient id="empty" cx="0.5" cy="0.5" r="0">
+                <stop offset="0" stop-color="red"/>
+                <stop offset="1" stop-color="lime"/>
+            </radialGradient>
+            <rect x="8" y="8" width="16" height="16" fill="url(#g)"/>
+            <rect x="32" y="8" width="16" height="16" fill="url(#empty)"/>
+        </svg>"#;
+        let tree = Tree::from_data(RADIAL_SVG.as_bytes(), &Options::default()).unwrap();
+        let scene = SVGScene::from_tree(&tree).scene;
+        assert_eq!(scene.draw_path_count(), 2);
+
+        let paint = scene.get_paint(scene.get_draw_path(DrawPathId(0)).paint());
+        let gradient = paint.gradient().unwrap();
+        assert!(matches!(gradient.geometry, GradientGeometry::Radial { .. }));
+        assert_eq!(gradient.stops().len(), 2);
+
+        // A zero radius collapses to the color of the last stop.
+        let paint = scene.get_paint(scene.get_draw_path(DrawPathId(1)).paint());
+        assert!(paint.is_color());
+        assert_eq!(paint.base_color(), ColorU::new(0, 255, 0, 255));
+    }
+
+    #[test]
+    fn test_stop_opacity_becomes_stop_alpha() {
+        const GRADIENT_SVG: &str = r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 64 64">
+            <linearGradient id="g" x1="0" y1="0" x2="1" y2="0">
+                <stop offset="0" stop-color="red"/>
+                <stop offset="1" stop-color="blue" stop-opacity="0.5"/>
+            </linearGradient>
+            <rect x="8" y="8" width="48" height="48" fill="url(#g)"/>
+        </svg>"#;
+        let tree = Tree::from_data(GRADIENT_SVG.as_bytes(), &Options::default()).unwrap();
+        let scene = SVGScene::from_tree(&tree).scene;
+        assert_eq!(scene.draw_path_count(), 1);
+        let paint = scene.get_paint(scene.get_draw_path(DrawPathId(0)).paint());
+        let stops = paint.gradient().unwrap().stops();
+        assert_eq!(stops.len(), 2);
+        assert_eq!(stops[0].color, ColorU::new(255, 0, 0, 255));
+        assert_eq!(stops[1].color, ColorU::new(0, 0, 255, 128));
+    }
+
+    #[test]
+    fn test_thin_strokes_are_widened_to_min_stroke_width() {
+        // Returns the thickness of a horizontal line stroked with the given width.
+        fn stroke_thickness(stroke_width: f32, min_stroke_width: f32) -> f32 {
+            let svg = format!(r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 64 64">
+                <path d="M 8 32 L 56 32" fill="none" stroke="black" stroke-width="{}"/>
+            </svg>"#, stroke_width);
+            let tree = Tree::from_data(svg.as_bytes(), &Options::default()).unwrap();
+            let options = SVGBuildOptions { min_stroke_width, ..SVGBuildOptions::default() };
+            let scene = SVGScene::from_tree_and_scene_with_options(&tree, Scene::new(), &options)
+                .scene;
+            assert_eq!(scene.draw_path_count(), 1);
+            scene.get_draw_path(DrawPathId(0)).outline().bounds().height()
+        }
+
+        assert_eq!(stroke_thickness(0.25, 1.5), 1.5);
+        assert_eq!(stroke_thickness(2.0, 1.5), 2.0);
+    }
+
+    #[test]
+    fn test_content_outside_view_box_is_clipped_unless_overflow_is_visible() {
+        const OVERFLOW_SVG: &str = r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 64 64">
+            <rect x="32" y="32" width="64" height="64" fill="black"/>
+            <rect x="80" y="0" width="16" height="16" fill="red"/>
+        </svg>"#;
+
+        // Returns the bounds of every path built with the given `overflow_visible` setting.
+        fn path_bounds(overflow_visible: bool) -> Vec<RectF> {
+            let tree = Tree::from_data(OVERFLOW_SVG.as_bytes(), &Options::default()).unwrap();
+            let options = SVGBuildOptions { overflow_visible, ..SVGBuildOptions::default() };
+            let scene = SVGScene::from_tree_and_scene_with_options(&tree, Scene::new(), &options)
+                .scene;
+            (0..scene.draw_path_count()).map(|index| {
+                scene.get_draw_path(DrawPathId(index)).outline().bounds()
+            }).collect()
+        }
+
+        // By default, the overflowing rect is cut at the view box and the rect entirely outside
+        // it is dropped.
+        assert_eq!(path_bounds(false), vec![RectF::new(vec2f(32.0, 32.0), vec2f(32.0, 32.0))]);
+        assert_eq!(path_bounds(true),
+                   vec![RectF::new(vec2f(32.0, 32.0), vec2f(64.0, 64.0)),
+                        RectF::new(vec2f(80.0, 0.0), vec2f(16.0, 16.0))]);
+    }
+
+    #[test]
+    fn test_unsupported_features_are_reported_per_node() {
+        // The image is a 1x1 PNG.
+        const UNSUPPORTED_SVG: &str = concat!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg""#,
+            r#" xmlns:xlink="http://www.w3.org/1999/xlink" viewBox="0 0 64 64">"#,
+            r#"<filter id="blur"><feGaussianBlur stdDeviation="2"/></filter>"#,
+            r#"<g id="blurred" filter="url(#blur)">"#,
+            r#"<rect width="32" height="32" fill="black"/>"#,
+            r#"</g>"#,
+            r#"<image id="picture" x="32" y="32" width="1" height="1" xlink:href="data:image/png;"#,
+            r#"base64,iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mNkYPhfDwAChwGA60"#,
+            r#"e6kgAAAABJRU5ErkJggg=="/>"#,
+            r#"</svg>"#);
+        let tree = Tree::from_data(UNSUPPORTED_SVG.as_bytes(), &Options::default()).unwrap();
+        let svg = SVGScene::from_tree(&tree);
+
+        let warnings: Vec<_> = svg.warnings.iter().map(|warning| {
+            (warning.kind, warning.node_id.as_str())
+        }).collect();
+        assert!(warnings.contains(&(BuildResultFlags::UNSUPPORTED_FILTER_ATTR, "blurred")),
+                "{:?}",
+                warnings);
+        assert!(warnings.contains(&(BuildResultFlags::UNSUPPORTED_IMAGE_NODE, "picture")),
+                "{:?}",
+                warnings);
+        assert!(svg.result_flags.contains(BuildResultFlags::UNSUPPORTED_FILTER_ATTR |
+                                          BuildResultFlags::UNSUPPORTED_IMAGE_NODE));
+
+        let warning = UnsupportedFeature {
+            node_id: "picture".to_owned(),
+            kind: BuildResultFlags::UNSUPPORTED_IMAGE_NODE,
+        };
+        assert_eq!(warning.to_string(), "<image> (node \"picture\")");
+    }
+}